@@ -1,36 +1,370 @@
+// Shared by both binaries; each one only uses part of this module.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
 use std::env;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 
+use chrono_tz::Tz;
+use crossterm::event::{KeyCode, KeyModifiers};
+
 pub const DEFAULT_MAX_NOTIFICATIONS: usize = 30;
 pub const DEFAULT_REFRESH_SIGNAL: u8 = 8;
 const MAX_REFRESH_SIGNAL: u8 = 30;
+pub const DEFAULT_BODY_TRUNCATE_WIDTH: usize = 112;
+const MIN_BODY_TRUNCATE_WIDTH: usize = 10;
+const MAX_BODY_TRUNCATE_WIDTH: usize = 1000;
+pub const DEFAULT_DBUS_SERVICE: &str = "org.freedesktop.Notifications";
+pub const DEFAULT_ITEM_SPACING: u16 = 1;
+const MAX_ITEM_SPACING: u16 = 2;
+pub const DEFAULT_RECENT_CLOSE_SECS: u64 = 300;
+pub const DEFAULT_LOGGER_START_RETRIES: u32 = 5;
+const MAX_LOGGER_START_RETRIES: u32 = 100;
+pub const DEFAULT_LOGGER_START_BACKOFF_SECS: u64 = 1;
 const DEFAULT_LOG_PATH: &str = "~/.local/state/notilog/log.jsonl";
+/// Environment variable that overrides `log_file_path` from the config file.
+pub const LOG_FILE_ENV: &str = "NOTITUI_LOG_FILE";
+const OPEN_COMMANDS_SECTION: &str = "open_commands";
+const RETENTION_SECTION: &str = "retention";
+const APP_ICONS_SECTION: &str = "app_icons";
+const KEYS_SECTION: &str = "keys";
+const CONFIG_FILE: &str = "notitui/config.toml";
+const LOG_FILE: &str = "notilog/log.jsonl";
+
+/// How the TUI treats Pango markup and ANSI escapes in notification bodies.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BodyMarkup {
+    /// Remove tags and escapes, decode entities (`&amp;`).
+    Strip,
+    /// Like `Strip`, but turn basic tags and ANSI colors into styles.
+    Render,
+    /// Show the body exactly as logged.
+    Raw,
+}
+
+impl BodyMarkup {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "strip" => Some(Self::Strip),
+            "render" => Some(Self::Render),
+            "raw" => Some(Self::Raw),
+            _ => None,
+        }
+    }
+}
+
+/// List mode `notitui` starts in, from `default_filter`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DefaultFilter {
+    History,
+    Missed,
+    Open,
+}
+
+impl DefaultFilter {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "history" => Some(Self::History),
+            "missed" => Some(Self::Missed),
+            "open" => Some(Self::Open),
+            _ => None,
+        }
+    }
+}
+
+/// TUI actions that can be rebound in the `[keys]` section.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum KeyAction {
+    Quit,
+    Search,
+    JumpToId,
+    Next,
+    Prev,
+    First,
+    Last,
+    NextSameApp,
+    PrevSameApp,
+    PageDown,
+    PageUp,
+    ToggleFilter,
+    Dismiss,
+    DismissAll,
+    Delete,
+    Visual,
+    Copy,
+    Edit,
+    ToggleTime,
+    ToggleSort,
+    Today,
+    Compact,
+    ToggleHeader,
+    ToggleBody,
+    Refresh,
+    Open,
+}
+
+impl KeyAction {
+    pub const ALL: [Self; 26] = [
+        Self::Quit,
+        Self::Search,
+        Self::JumpToId,
+        Self::Next,
+        Self::Prev,
+        Self::First,
+        Self::Last,
+        Self::NextSameApp,
+        Self::PrevSameApp,
+        Self::PageDown,
+        Self::PageUp,
+        Self::ToggleFilter,
+        Self::Dismiss,
+        Self::DismissAll,
+        Self::Delete,
+        Self::Visual,
+        Self::Copy,
+        Self::Edit,
+        Self::ToggleTime,
+        Self::ToggleSort,
+        Self::Today,
+        Self::Compact,
+        Self::ToggleHeader,
+        Self::ToggleBody,
+        Self::Refresh,
+        Self::Open,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::Search => "search",
+            Self::JumpToId => "jump_to_id",
+            Self::Next => "next",
+            Self::Prev => "prev",
+            Self::First => "first",
+            Self::Last => "last",
+            Self::NextSameApp => "next_same_app",
+            Self::PrevSameApp => "prev_same_app",
+            Self::PageDown => "page_down",
+            Self::PageUp => "page_up",
+            Self::ToggleFilter => "toggle_filter",
+            Self::Dismiss => "dismiss",
+            Self::DismissAll => "dismiss_all",
+            Self::Delete => "delete",
+            Self::Visual => "visual",
+            Self::Copy => "copy",
+            Self::Edit => "edit",
+            Self::ToggleTime => "toggle_time",
+            Self::ToggleSort => "toggle_sort",
+            Self::Today => "today",
+            Self::Compact => "compact",
+            Self::ToggleHeader => "toggle_header",
+            Self::ToggleBody => "toggle_body",
+            Self::Refresh => "refresh",
+            Self::Open => "open",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// A key code plus its Ctrl/Alt modifiers (Shift is implied by the character).
+pub type KeyBinding = (KeyCode, KeyModifiers);
+
+/// Parses key specs such as `j`, `G`, `space`, `pagedown`, `f5`, `C-n`
+/// (or `ctrl-n`), and `A-x` (or `alt-x`).
+pub fn parse_key_spec(spec: &str) -> Option<KeyBinding> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = spec.trim();
+    loop {
+        let lower = rest.to_ascii_lowercase();
+        let (modifier, prefix_len) = if lower.starts_with("c-") {
+            (KeyModifiers::CONTROL, 2)
+        } else if lower.starts_with("ctrl-") {
+            (KeyModifiers::CONTROL, 5)
+        } else if lower.starts_with("a-") || lower.starts_with("m-") {
+            (KeyModifiers::ALT, 2)
+        } else if lower.starts_with("alt-") {
+            (KeyModifiers::ALT, 4)
+        } else {
+            break;
+        };
+        // A trailing `-` is the key itself (`C--`), not another prefix.
+        if rest.len() == prefix_len {
+            break;
+        }
+        modifiers |= modifier;
+        rest = &rest[prefix_len..];
+    }
+
+    let mut chars = rest.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(ch), None) => KeyCode::Char(ch),
+        _ => match rest.to_ascii_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            name => {
+                let number = name.strip_prefix('f')?.parse::<u8>().ok()?;
+                if !(1..=12).contains(&number) {
+                    return None;
+                }
+                KeyCode::F(number)
+            }
+        },
+    };
+    Some((code, modifiers))
+}
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub log_file_path: PathBuf,
     pub max_notification_length: usize,
+    pub max_age_days: Option<u64>,
+    /// Skip a notification identical to one logged this many seconds ago (0 = off).
+    pub dedup_window_secs: u64,
     pub refresh_signal: u8,
+    /// Bus names `notilog logger run` monitors (comma-separated in the file).
+    pub dbus_services: Vec<String>,
+    /// Extra attempts to start `busctl monitor` before the logger gives up.
+    pub logger_start_retries: u32,
+    /// Wait before the first retry; doubled after each failed attempt.
+    pub logger_start_backoff_secs: u64,
+    pub body_truncate_width: usize,
+    pub wrap_body: bool,
+    /// Blank rows between notifications in the TUI list (`0..=2`).
+    pub item_spacing: u16,
+    pub body_markup: BodyMarkup,
+    pub mouse_capture: bool,
+    /// Start mode for the TUI; `None` keeps the mode saved at the last exit.
+    pub default_filter: Option<DefaultFilter>,
+    /// Leave out notifications whose summary and body are both empty.
+    pub hide_empty_summary: bool,
+    /// Emphasize notifications closed this many seconds ago or less (0 = off).
+    pub recent_close_secs: u64,
+    /// Fixed zone for displayed times; `None` uses the system local zone.
+    pub timezone: Option<Tz>,
+    /// Launch commands keyed by lowercased `app_name`, from `[open_commands]`.
+    pub open_commands: HashMap<String, String>,
+    /// Glyphs shown before summaries in the TUI, keyed by lowercased
+    /// `app_name` (`*` for all other apps), from `[app_icons]`.
+    pub app_icons: HashMap<String, String>,
+    /// Per-app notification caps keyed by lowercased `app_name`, from `[retention]`.
+    pub app_retention: HashMap<String, usize>,
+    /// Keys for actions listed in `[keys]`; each replaces that action's defaults.
+    pub key_bindings: HashMap<KeyAction, Vec<KeyBinding>>,
+}
+
+/// Replaces `path` with what `write` puts in a temp file next to it, synced
+/// before the rename, so a crash leaves either the old or the new file, never
+/// a partial one.
+pub fn replace_file(
+    path: &Path,
+    write: impl FnOnce(&mut io::BufWriter<&File>) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(path.file_name().unwrap_or_default());
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = File::create(&temp_path).and_then(|file| {
+        let mut writer = io::BufWriter::new(&file);
+        write(&mut writer)?;
+        writer.flush()?;
+        drop(writer);
+        file.sync_all()
+    });
+    result
+        .and_then(|()| fs::rename(&temp_path, path))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&temp_path);
+        })
+}
+
+pub fn config_path() -> PathBuf {
+    let home = home_dir();
+    xdg_dir("XDG_CONFIG_HOME", &home, ".config").join(CONFIG_FILE)
 }
 
 pub fn load_or_create() -> AppConfig {
+    load_with_issues().0
+}
+
+/// Loads the config like `load_or_create`, also returning one message per
+/// ignored line (unknown keys or sections, malformed or out-of-range values).
+pub fn load_with_issues() -> (AppConfig, Vec<String>) {
     let home = home_dir();
-    let config_path = home.join(".config/notitui/config.toml");
-    ensure_default_config_file(&config_path);
+    let config_path = config_path();
+    let default_log_path = default_log_path_setting(&home);
+    ensure_default_config_file(&config_path, &default_log_path);
 
-    let mut log_file_path = expand_path(DEFAULT_LOG_PATH, &home);
+    let mut log_file_path = expand_path(&default_log_path, &home);
     let mut max_notification_length = DEFAULT_MAX_NOTIFICATIONS;
+    let mut max_age_days = None;
+    let mut dedup_window_secs = 0;
     let mut refresh_signal = DEFAULT_REFRESH_SIGNAL;
+    let mut dbus_services = vec![String::from(DEFAULT_DBUS_SERVICE)];
+    let mut logger_start_retries = DEFAULT_LOGGER_START_RETRIES;
+    let mut logger_start_backoff_secs = DEFAULT_LOGGER_START_BACKOFF_SECS;
+    let mut body_truncate_width = DEFAULT_BODY_TRUNCATE_WIDTH;
+    let mut wrap_body = false;
+    let mut item_spacing = DEFAULT_ITEM_SPACING;
+    let mut body_markup = BodyMarkup::Strip;
+    let mut mouse_capture = true;
+    let mut default_filter = None;
+    let mut hide_empty_summary = false;
+    let mut recent_close_secs = DEFAULT_RECENT_CLOSE_SECS;
+    let mut timezone = None;
+    let mut open_commands = HashMap::new();
+    let mut app_icons = HashMap::new();
+    let mut app_retention = HashMap::new();
+    let mut key_bindings = HashMap::new();
+    let mut section = String::new();
+    let mut issues = Vec::new();
 
     if let Ok(content) = fs::read_to_string(&config_path) {
-        for line in content.lines() {
+        for (index, line) in content.lines().enumerate() {
+            let line_number = index + 1;
             let stripped = line.split('#').next().unwrap_or("").trim();
             if stripped.is_empty() {
                 continue;
             }
 
+            if let Some(name) = stripped
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                section = name.trim().to_string();
+                if ![
+                    OPEN_COMMANDS_SECTION,
+                    RETENTION_SECTION,
+                    APP_ICONS_SECTION,
+                    KEYS_SECTION,
+                ]
+                .contains(&section.as_str())
+                {
+                    issues.push(format!("line {line_number}: unknown section [{section}]"));
+                }
+                continue;
+            }
+
             let Some((key, value)) = stripped.split_once('=') else {
+                issues.push(format!("line {line_number}: expected `key = value`"));
                 continue;
             };
             let key = key.trim();
@@ -39,44 +373,248 @@ pub fn load_or_create() -> AppConfig {
                 continue;
             }
 
-            match key {
+            if section == KEYS_SECTION {
+                let Some(action) = KeyAction::from_name(key) else {
+                    issues.push(format!("line {line_number}: unknown action `{key}`"));
+                    continue;
+                };
+                let mut bindings = Vec::new();
+                for spec in value.split(',').filter(|spec| !spec.trim().is_empty()) {
+                    match parse_key_spec(spec) {
+                        Some(binding) => bindings.push(binding),
+                        None => issues.push(format!(
+                            "line {line_number}: invalid key `{}` for `{key}`",
+                            spec.trim()
+                        )),
+                    }
+                }
+                if !bindings.is_empty() {
+                    key_bindings.insert(action, bindings);
+                }
+                continue;
+            }
+
+            if !section.is_empty() {
+                let app_name = key
+                    .trim_matches('"')
+                    .trim_matches('\'')
+                    .trim()
+                    .to_lowercase();
+                if app_name.is_empty() {
+                    issues.push(format!("line {line_number}: missing app name"));
+                    continue;
+                }
+                match section.as_str() {
+                    OPEN_COMMANDS_SECTION => {
+                        open_commands.insert(app_name, value.to_string());
+                    }
+                    APP_ICONS_SECTION => {
+                        app_icons.insert(app_name, value.to_string());
+                    }
+                    RETENTION_SECTION => match value.parse::<usize>() {
+                        Ok(parsed) if parsed > 0 => {
+                            app_retention.insert(app_name, parsed);
+                        }
+                        _ => issues.push(format!(
+                            "line {line_number}: invalid retention for `{key}`: `{value}` (expected a positive integer)"
+                        )),
+                    },
+                    _ => {}
+                }
+                continue;
+            }
+
+            let valid = match key {
                 "log_file_path" => {
                     log_file_path = expand_path(value, &home);
+                    true
                 }
-                "max_notification_length" | "max_notifications" => {
-                    if let Ok(parsed) = value.parse::<usize>() {
-                        if parsed > 0 {
-                            max_notification_length = parsed;
-                        }
+                "max_notification_length" | "max_notifications" => match value.parse::<usize>() {
+                    Ok(parsed) if parsed > 0 => {
+                        max_notification_length = parsed;
+                        true
                     }
-                }
+                    _ => false,
+                },
+                "max_age_days" => match value.parse::<u64>() {
+                    Ok(parsed) => {
+                        max_age_days = (parsed > 0).then_some(parsed);
+                        true
+                    }
+                    Err(_) => false,
+                },
+                "dedup_window_secs" => match value.parse::<u64>() {
+                    Ok(parsed) => {
+                        dedup_window_secs = parsed;
+                        true
+                    }
+                    Err(_) => false,
+                },
                 "refresh_signal"
                 | "refresh_signal_channel"
                 | "waybar_signal"
-                | "waybar_signal_channel" => {
-                    if let Ok(parsed) = value.parse::<u8>() {
-                        if parsed <= MAX_REFRESH_SIGNAL {
-                            refresh_signal = parsed;
+                | "waybar_signal_channel" => match value.parse::<u8>() {
+                    Ok(parsed) if parsed <= MAX_REFRESH_SIGNAL => {
+                        refresh_signal = parsed;
+                        true
+                    }
+                    _ => false,
+                },
+                "dbus_service" | "dbus_services" => {
+                    let services = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|service| !service.is_empty())
+                        .map(String::from)
+                        .collect::<Vec<_>>();
+                    let valid = !services.is_empty()
+                        && services.iter().all(|service| {
+                            service.contains('.')
+                                && service
+                                    .chars()
+                                    .all(|ch| ch.is_ascii_alphanumeric() || "._-:".contains(ch))
+                        });
+                    if valid {
+                        dbus_services = services;
+                    }
+                    valid
+                }
+                "logger_start_retries" => match value.parse::<u32>() {
+                    Ok(parsed) if parsed <= MAX_LOGGER_START_RETRIES => {
+                        logger_start_retries = parsed;
+                        true
+                    }
+                    _ => false,
+                },
+                "logger_start_backoff_secs" => match value.parse::<u64>() {
+                    Ok(parsed) if parsed > 0 => {
+                        logger_start_backoff_secs = parsed;
+                        true
+                    }
+                    _ => false,
+                },
+                "body_truncate_width" => match value.parse::<usize>() {
+                    Ok(0) => {
+                        body_truncate_width = 0;
+                        true
+                    }
+                    Ok(parsed) => {
+                        body_truncate_width =
+                            parsed.clamp(MIN_BODY_TRUNCATE_WIDTH, MAX_BODY_TRUNCATE_WIDTH);
+                        if body_truncate_width != parsed {
+                            issues.push(format!(
+                                "line {line_number}: body_truncate_width {parsed} clamped to {body_truncate_width}"
+                            ));
                         }
+                        true
+                    }
+                    Err(_) => false,
+                },
+                "wrap_body" => match value.parse::<bool>() {
+                    Ok(parsed) => {
+                        wrap_body = parsed;
+                        true
+                    }
+                    Err(_) => false,
+                },
+                "item_spacing" => match value.parse::<u16>() {
+                    Ok(parsed) if parsed <= MAX_ITEM_SPACING => {
+                        item_spacing = parsed;
+                        true
+                    }
+                    _ => false,
+                },
+                "body_markup" => match BodyMarkup::parse(value) {
+                    Some(parsed) => {
+                        body_markup = parsed;
+                        true
+                    }
+                    None => false,
+                },
+                "mouse_capture" => match value.parse::<bool>() {
+                    Ok(parsed) => {
+                        mouse_capture = parsed;
+                        true
+                    }
+                    Err(_) => false,
+                },
+                "default_filter" => {
+                    // A typo still pins a start mode: the default one.
+                    let parsed = DefaultFilter::parse(value);
+                    default_filter = Some(parsed.unwrap_or(DefaultFilter::Missed));
+                    parsed.is_some()
+                }
+                "hide_empty_summary" => match value.parse::<bool>() {
+                    Ok(parsed) => {
+                        hide_empty_summary = parsed;
+                        true
+                    }
+                    Err(_) => false,
+                },
+                "recent_close_secs" => match value.parse::<u64>() {
+                    Ok(parsed) => {
+                        recent_close_secs = parsed;
+                        true
+                    }
+                    Err(_) => false,
+                },
+                "timezone" => match value.parse::<Tz>() {
+                    Ok(parsed) => {
+                        timezone = Some(parsed);
+                        true
                     }
+                    Err(_) => false,
+                },
+                _ => {
+                    issues.push(format!("line {line_number}: unknown key `{key}`"));
+                    true
                 }
-                _ => {}
+            };
+            if !valid {
+                issues.push(format!(
+                    "line {line_number}: invalid value for `{key}`: `{value}`"
+                ));
             }
         }
     }
 
+    if let Ok(value) = env::var(LOG_FILE_ENV)
+        && !value.trim().is_empty()
+    {
+        log_file_path = expand_path(value.trim(), &home);
+    }
+
     if let Some(parent) = log_file_path.parent() {
         let _ = fs::create_dir_all(parent);
     }
 
-    AppConfig {
+    let config = AppConfig {
         log_file_path,
         max_notification_length,
+        max_age_days,
+        dedup_window_secs,
         refresh_signal,
-    }
+        dbus_services,
+        logger_start_retries,
+        logger_start_backoff_secs,
+        body_truncate_width,
+        wrap_body,
+        item_spacing,
+        body_markup,
+        mouse_capture,
+        default_filter,
+        hide_empty_summary,
+        recent_close_secs,
+        timezone,
+        open_commands,
+        app_icons,
+        app_retention,
+        key_bindings,
+    };
+    (config, issues)
 }
 
-fn ensure_default_config_file(path: &Path) {
+fn ensure_default_config_file(path: &Path, default_log_path: &str) {
     if path.exists() {
         return;
     }
@@ -86,7 +624,7 @@ fn ensure_default_config_file(path: &Path) {
     }
 
     let default = format!(
-        "# notitui/notilog config\n# Notification log file path\nlog_file_path = \"{DEFAULT_LOG_PATH}\"\n\n# Maximum number of notifications to keep\nmax_notification_length = {DEFAULT_MAX_NOTIFICATIONS}\n\n# Refresh signal channel (RTMIN+N)\n# Valid range: 0..={MAX_REFRESH_SIGNAL}\nrefresh_signal = {DEFAULT_REFRESH_SIGNAL}\n"
+        "# notitui/notilog config\n# Notification log file path\nlog_file_path = \"{default_log_path}\"\n\n# Maximum number of notifications to keep\nmax_notification_length = {DEFAULT_MAX_NOTIFICATIONS}\n\n# Also drop notifications older than this many days (0 = no age limit).\n# When both limits are set, a notification is kept only if it satisfies both.\nmax_age_days = 0\n\n# Skip a notification whose app, summary, and body match one logged within\n# this many seconds (0 = log every notification)\ndedup_window_secs = 0\n\n# Refresh signal channel (RTMIN+N)\n# Valid range: 0..={MAX_REFRESH_SIGNAL}\nrefresh_signal = {DEFAULT_REFRESH_SIGNAL}\n\n# D-Bus service(s) the logger monitors; separate several with commas\ndbus_service = \"{DEFAULT_DBUS_SERVICE}\"\n\n# Retries when busctl cannot start (e.g. D-Bus not up yet at boot); the wait\n# starts at logger_start_backoff_secs and doubles after each attempt\n# Valid range: 0..={MAX_LOGGER_START_RETRIES}\nlogger_start_retries = {DEFAULT_LOGGER_START_RETRIES}\nlogger_start_backoff_secs = {DEFAULT_LOGGER_START_BACKOFF_SECS}\n\n# Body line truncation width in the TUI (0 = no truncation)\n# Valid range: {MIN_BODY_TRUNCATE_WIDTH}..={MAX_BODY_TRUNCATE_WIDTH}\nbody_truncate_width = {DEFAULT_BODY_TRUNCATE_WIDTH}\n\n# Wrap body lines to the list width instead of truncating them\nwrap_body = false\n\n# Blank rows between notifications in the TUI list\n# Valid range: 0..={MAX_ITEM_SPACING}\nitem_spacing = {DEFAULT_ITEM_SPACING}\n\n# Pango markup/ANSI escapes in bodies: strip, render (as styles), or raw\nbody_markup = \"strip\"\n\n# Capture the mouse in the TUI (false keeps native terminal text selection)\nmouse_capture = true\n\n# Mode the TUI starts in: history, missed, or open; unset = the mode it was\n# last closed in\n# default_filter = \"history\"\n\n# Hide notifications with an empty summary and body in the TUI (e.g. blank heartbeats)\nhide_empty_summary = false\n\n# Emphasize notifications closed within this many seconds in the TUI (0 = off)\nrecent_close_secs = {DEFAULT_RECENT_CLOSE_SECS}\n\n# IANA timezone for displayed times (TUI clock, export --iso); unset = system local\n# timezone = \"UTC\"\n\n# Sections below must come after all the settings above. [{OPEN_COMMANDS_SECTION}],\n# [{RETENTION_SECTION}], and [{APP_ICONS_SECTION}] are keyed by notification app name\n# (matched case-insensitively).\n\n# Commands run by Enter in the TUI\n# [{OPEN_COMMANDS_SECTION}]\n# Slack = \"slack\"\n\n# Per-app caps on kept notifications, applied before max_notification_length\n# [{RETENTION_SECTION}]\n# \"System Updates\" = 3\n\n# Short glyphs shown before summaries in the TUI; \"*\" covers all other apps\n# [{APP_ICONS_SECTION}]\n# Slack = \"💬\"\n# \"*\" = \"•\"\n\n# TUI key bindings by action; listing an action replaces its default keys\n# [{KEYS_SECTION}]\n# next = \"j, down, C-n\"\n# prev = \"k, up, C-p\"\n"
     );
     let _ = fs::write(path, default);
 }
@@ -98,6 +636,27 @@ fn home_dir() -> PathBuf {
     env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
 }
 
+/// Resolves an XDG base directory, falling back to `~/<fallback>` when the
+/// variable is unset, empty, or not absolute (as the spec requires).
+fn xdg_dir(var: &str, home: &Path, fallback: &str) -> PathBuf {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| home.join(fallback))
+}
+
+fn default_log_path_setting(home: &Path) -> String {
+    match env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+    {
+        Some(state_home) if state_home != home.join(".local/state") => {
+            state_home.join(LOG_FILE).display().to_string()
+        }
+        _ => String::from(DEFAULT_LOG_PATH),
+    }
+}
+
 fn expand_path(input: &str, home: &Path) -> PathBuf {
     if input == "~" {
         return home.to_path_buf();
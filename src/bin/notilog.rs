@@ -1,16 +1,69 @@
-use std::collections::{HashMap, HashSet};
+use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::env;
 use std::fs::{self, File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
-use std::time::{SystemTime, UNIX_EPOCH};
-
+use std::io::{BufRead, BufReader, IsTerminal, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Local};
+use chrono_tz::Tz;
+use flate2::Compression;
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use regex::Regex;
 use serde_json::{Value, json};
 
 #[path = "../app_config.rs"]
 mod app_config;
 
+const ORPHAN_CLOSE_WINDOW_SECS: i64 = 24 * 60 * 60;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+// Newest epoch written by `export --since-last`, stored next to the log file.
+const EXPORT_CURSOR_FILE: &str = "export_cursor";
+// Written by `logger --daemon`, next to the log file.
+const LOGGER_PID_FILE: &str = "logger.pid";
+// busctl that is still running after this long is taken as started.
+const MONITOR_STARTUP_GRACE: Duration = Duration::from_millis(300);
+const MAX_MONITOR_BACKOFF: Duration = Duration::from_secs(60);
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// `doctor` warns when nothing was logged for this long.
+const DOCTOR_RECENT_SECS: i64 = 24 * 60 * 60;
+
+static LOG_FILE_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+// Standard input read up front by `--stdin`; replaces the log for reading.
+static STDIN_LOG: OnceLock<Vec<u8>> = OnceLock::new();
+const STDIN_COMMANDS: &[&str] = &[
+    "export", "tail", "replay", "stats", "query", "last", "lookup",
+];
+// Set once by `logger --log-level`/`--verbose`; unset means `LogLevel::Error`.
+static LOG_LEVEL: OnceLock<LogLevel> = OnceLock::new();
+
+/// How much parser diagnostics `logger` prints to stderr.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+enum LogLevel {
+    Error,
+    Warn,
+    Debug,
+}
+
+impl LogLevel {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "error" => Some(Self::Error),
+            "warn" => Some(Self::Warn),
+            "debug" => Some(Self::Debug),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct PendingNotify {
     timestamp: String,
@@ -19,6 +72,223 @@ struct PendingNotify {
     body: String,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum ExportFormat {
+    Json,
+    JsonLines,
+    Markdown,
+}
+
+/// How exports print a record's time.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum TimeFormat {
+    /// The `HH:MM` logged with the event.
+    Logged,
+    /// RFC 3339 in the configured zone (`None` is the local zone).
+    Iso(Option<Tz>),
+}
+
+#[derive(Debug, Clone)]
+struct ExportOptions {
+    format: ExportFormat,
+    pretty: bool,
+    app: Option<String>,
+    iso: bool,
+    app_summary: bool,
+    since_last: bool,
+    reset_cursor: bool,
+    count_only: bool,
+    fields: Option<Vec<String>>,
+    merge_bodies: bool,
+    oldest_first: bool,
+    gzip: bool,
+    output: Option<PathBuf>,
+    /// Apps left out of the export, from `--app-filter-file`.
+    excluded_apps: Vec<String>,
+}
+
+impl ExportOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        const USAGE: &str = "usage: notilog export [--format json|jsonl|md] [--pretty] [--app <name>] [--iso] [--app-summary] [--since-last] [--reset-cursor] [--count-only] [--fields a,b,c] [--merge-bodies] [--order asc|desc] [--gzip] [--output <file>] [--app-filter-file <file>]";
+        let mut options = Self {
+            format: ExportFormat::Json,
+            pretty: false,
+            app: None,
+            iso: false,
+            app_summary: false,
+            since_last: false,
+            reset_cursor: false,
+            count_only: false,
+            fields: None,
+            merge_bodies: false,
+            oldest_first: false,
+            gzip: false,
+            output: None,
+            excluded_apps: Vec::new(),
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--format" => {
+                    options.format = match iter.next().map(String::as_str) {
+                        Some("json") => ExportFormat::Json,
+                        Some("jsonl") => ExportFormat::JsonLines,
+                        Some("md") => ExportFormat::Markdown,
+                        _ => return Err(String::from(USAGE)),
+                    };
+                }
+                "--pretty" => options.pretty = true,
+                "--app" => {
+                    options.app = Some(iter.next().ok_or_else(|| String::from(USAGE))?.clone());
+                }
+                "--iso" => options.iso = true,
+                "--app-summary" => options.app_summary = true,
+                "--since-last" => options.since_last = true,
+                "--reset-cursor" => options.reset_cursor = true,
+                "--count-only" => options.count_only = true,
+                "--merge-bodies" => options.merge_bodies = true,
+                "--order" => {
+                    options.oldest_first = match iter.next().map(String::as_str) {
+                        Some("asc") => true,
+                        Some("desc") => false,
+                        _ => return Err(String::from(USAGE)),
+                    };
+                }
+                "--fields" => {
+                    let value = iter.next().ok_or_else(|| String::from(USAGE))?;
+                    options.fields = Some(parse_export_fields(value)?);
+                }
+                "--app-filter-file" => {
+                    let path = iter.next().ok_or_else(|| String::from(USAGE))?;
+                    let text = fs::read_to_string(path)
+                        .map_err(|error| format!("could not read {path}: {error}"))?;
+                    options.excluded_apps.extend(parse_app_filter(&text));
+                }
+                "--gzip" => options.gzip = true,
+                "--output" | "-o" => {
+                    let value = iter.next().ok_or_else(|| String::from(USAGE))?;
+                    options.output = Some(PathBuf::from(value));
+                }
+                _ => return Err(String::from(USAGE)),
+            }
+        }
+
+        if options.pretty && options.format == ExportFormat::JsonLines {
+            return Err(String::from(
+                "--pretty cannot be combined with --format jsonl",
+            ));
+        }
+        if options.format == ExportFormat::Markdown
+            && (options.pretty || options.app_summary || options.fields.is_some())
+        {
+            return Err(String::from(
+                "--format md cannot be combined with --pretty, --app-summary, or --fields",
+            ));
+        }
+        if options.fields.is_some() && options.app_summary {
+            return Err(String::from(
+                "--fields cannot be combined with --app-summary",
+            ));
+        }
+        if options.merge_bodies
+            && (options.app_summary
+                || options.fields.is_some()
+                || options.format == ExportFormat::Markdown)
+        {
+            return Err(String::from(
+                "--merge-bodies cannot be combined with --app-summary, --fields, or --format md",
+            ));
+        }
+        if options.count_only && options.app_summary {
+            return Err(String::from(
+                "--count-only cannot be combined with --app-summary",
+            ));
+        }
+        // One cursor is shared by every run, so a filtered run would move it
+        // past records of apps it left out.
+        if options.since_last && (options.app.is_some() || !options.excluded_apps.is_empty()) {
+            return Err(String::from(
+                "--since-last cannot be combined with --app or --app-filter-file",
+            ));
+        }
+        if options.count_only && (options.gzip || options.output.is_some()) {
+            return Err(String::from(
+                "--count-only cannot be combined with --gzip or --output",
+            ));
+        }
+        // Compressed bytes would only garble the terminal.
+        if options.gzip && options.output.is_none() && std::io::stdout().is_terminal() {
+            return Err(String::from(
+                "refusing to write gzip data to a terminal; pipe it or use --output <file>",
+            ));
+        }
+
+        Ok(options)
+    }
+}
+
+/// Names accepted by `export --fields`: the `record_to_json` keys, the `--iso`
+/// keys, and `time` (the `--app-summary` time).
+const EXPORT_FIELDS: &[&str] = &[
+    "event_uid",
+    "id",
+    "epoch",
+    "hhmm",
+    "app_name",
+    "summary",
+    "body_source",
+    "body",
+    "close_reason_code",
+    "close_reason",
+    "closed_epoch",
+    "closed_hhmm",
+    "time",
+    "seq",
+    "epoch_iso",
+    "closed_epoch_iso",
+];
+
+/// App names from an `--app-filter-file`: one per line, blank lines and lines
+/// starting with `#` skipped.
+fn parse_app_filter(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn parse_export_fields(value: &str) -> Result<Vec<String>, String> {
+    let fields = value
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(|field| {
+            if EXPORT_FIELDS.contains(&field) {
+                Ok(field.to_string())
+            } else {
+                Err(format!(
+                    "unknown field `{field}`; valid fields: {}",
+                    EXPORT_FIELDS.join(", ")
+                ))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut seen = HashSet::new();
+    let fields = fields
+        .into_iter()
+        .filter(|field| seen.insert(field.clone()))
+        .collect::<Vec<_>>();
+    if fields.is_empty() {
+        return Err(format!(
+            "--fields expects a comma-separated list of: {}",
+            EXPORT_FIELDS.join(", ")
+        ));
+    }
+    Ok(fields)
+}
+
 #[derive(Debug, Clone)]
 struct LogRecord {
     event_uid: Option<String>,
@@ -33,6 +303,9 @@ struct LogRecord {
     close_reason: Option<String>,
     closed_epoch: Option<i64>,
     closed_hhmm: Option<String>,
+    /// Ingest order stamped on append; breaks ties between equal (or missing)
+    /// timestamps.
+    seq: Option<u64>,
 }
 
 impl LogRecord {
@@ -50,6 +323,7 @@ impl LogRecord {
             close_reason: None,
             closed_epoch: None,
             closed_hhmm: None,
+            seq: None,
         }
     }
 
@@ -87,20 +361,54 @@ impl LogRecord {
         if other.closed_hhmm.is_some() {
             self.closed_hhmm = other.closed_hhmm.clone();
         }
+        if other.seq.is_some() {
+            self.seq = other.seq;
+        }
     }
 }
 
 fn main() {
-    let mut args = env::args().skip(1);
-    let result = match args.next().as_deref() {
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("--log-file") {
+        args.next();
+        let Some(path) = args.next() else {
+            eprintln!("--log-file expects a path");
+            std::process::exit(1);
+        };
+        let _ = LOG_FILE_OVERRIDE.set(PathBuf::from(path));
+    }
+
+    let command = args.next();
+    let mut args = args.collect::<Vec<_>>();
+    if let Some(index) = args.iter().position(|arg| arg == "--stdin") {
+        args.remove(index);
+        if let Err(error) = load_stdin_log(command.as_deref()) {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    }
+    let args = args.into_iter();
+
+    let result = match command.as_deref() {
         Some("logger") => handle_logger(args.collect()),
         Some("mark-user") => handle_mark_user(args.collect()),
+        Some("unmark") => handle_unmark(args.collect()),
         Some("tail") => handle_tail(args.collect()),
-        Some("export") => handle_export(),
-        Some("stats") => handle_stats(),
+        Some("replay") => handle_replay(args.collect()),
+        Some("export") => handle_export(args.collect()),
+        Some("stats") => handle_stats(args.collect()),
+        Some("watch") => handle_watch(args.collect()),
         Some("query") => handle_query(args.collect()),
+        Some("last") => handle_last(args.collect()),
         Some("lookup") => handle_lookup(args.collect()),
         Some("prune") => handle_prune(args.collect()),
+        Some("purge-app") => handle_purge_app(args.collect()),
+        Some("import") => handle_import(args.collect()),
+        Some("merge-logs") => handle_merge_logs(args.collect()),
+        Some("vacuum") => handle_vacuum(),
+        Some("config") => handle_config(args.collect()),
+        Some("doctor") => handle_doctor(args.collect()),
+        Some("json-schema") => handle_json_schema(args.collect()),
         _ => {
             print_help();
             Ok(())
@@ -113,27 +421,241 @@ fn main() {
     }
 }
 
+/// Buffers standard input as the log for `--stdin`; only read-only
+/// commands accept it.
+fn load_stdin_log(command: Option<&str>) -> Result<(), String> {
+    if !command.is_some_and(|command| STDIN_COMMANDS.contains(&command)) {
+        return Err(format!(
+            "--stdin only works with read commands: {}",
+            STDIN_COMMANDS.join(", ")
+        ));
+    }
+    let mut bytes = Vec::new();
+    std::io::stdin()
+        .read_to_end(&mut bytes)
+        .map_err(|error| format!("could not read stdin: {error}"))?;
+    let _ = STDIN_LOG.set(bytes);
+    Ok(())
+}
+
+/// The `--stdin` log, when one was given.
+fn stdin_log() -> Option<&'static [u8]> {
+    STDIN_LOG.get().map(Vec::as_slice)
+}
+
+/// Whether there is a log to read at `path` (always true with `--stdin`).
+fn log_exists(path: &Path) -> bool {
+    stdin_log().is_some() || path.exists()
+}
+
+/// `path` for messages, or `<stdin>` with `--stdin`.
+fn log_display(path: &Path) -> String {
+    match stdin_log() {
+        Some(_) => String::from("<stdin>"),
+        None => path.display().to_string(),
+    }
+}
+
 fn print_help() {
     println!("notilog - notification logger and reader");
+    println!("\nUsage: notilog [--log-file <path>] <command> [--stdin]");
     println!("\nCommands:");
     println!("  logger run                Listen on D-Bus and append notification events");
+    println!("  logger --daemon           Run the logger in the background (PID in logger.pid)");
+    println!("  logger status | stop      Check or stop the background logger");
+    println!("  logger --once             Parse busctl monitor output from stdin");
+    println!("  logger --replay <file>    Parse a captured busctl monitor dump");
+    println!("  logger ... --verbose      Print parser diagnostics (or --log-level warn|debug)");
     println!("  mark-user --event <uid>   Mark close reason as dismissed-by-user");
-    println!("  export                    Print merged records as JSON array");
-    println!("  tail [--n N]              Show the last N raw log records (default 20)");
-    println!("  stats                     Show log path and record count");
-    println!("  query --id <id>           Show merged record for one notification id");
+    println!("  mark-user --all-expired   Mark every expired notification as dismissed-by-user");
+    println!("  unmark --event <uid>      Revert a dismissed-by-user mark to expired");
+    println!("  export [options]          Print merged records (see export options below)");
+    println!("  tail [--n N] [--app A]    Show the last N raw log records (default 20)");
+    println!("  tail ... --reason R       Only records closed with reason R (code or label)");
+    println!("  tail ... --follow         Keep printing records as they are logged (-f)");
+    println!(
+        "  tail ... --format json    One JSON object per record instead of `#id HH:MM summary` (--json)"
+    );
+    println!("  replay [--speed X]        Re-print records with their real gaps, X times faster");
+    println!("  stats [--json]            Show log path and record count");
+    println!("  watch [--interval N]      Redraw stats every N seconds (default 5)");
+    println!("  query --id <id>           Show merged record for one notification id [--pretty]");
+    println!("  query --event <uid>       Show merged record for one event uid [--pretty]");
+    println!("  query --summary <text>    Search summaries (case-insensitive) [--limit N]");
+    println!("  query --body-regex <re>   Search bodies by regular expression [--limit N]");
+    println!("  query ... --count-only    Print only the number of matching records");
+    println!("  last [--format json|line] Print the newest notification (JSON or `HH:MM summary`)");
     println!("  lookup --ids <a,b,c>      Print JSON map of id to HH:MM");
     println!("  prune --days <days>       Remove records older than N days");
+    println!("  prune --keep <n>          Keep only the newest N notifications");
+    println!("  prune ... --dry-run       Show what prune would remove without writing");
+    println!(
+        "  purge-app <name>          Remove every notification from one app (case-insensitive)"
+    );
+    println!("  import <file>             Append records from another JSONL log");
+    println!(
+        "  merge-logs <file>...      Combine logs into the configured log (or --output <file>)"
+    );
+    println!("  vacuum                    Drop duplicate and corrupt lines from the log");
+    println!("  config path               Print the config file location");
+    println!("  config show               Print the effective config as JSON");
+    println!("  config check              Report unknown keys and invalid values in the config");
+    println!(
+        "  doctor                    Check config, log file, busctl, D-Bus, and recent activity"
+    );
+    println!("  json-schema               Print the JSON Schema of an exported record");
+    println!("\nExport options:");
+    println!(
+        "  --format json|jsonl|md    JSON array (default), one record per line, or Markdown table"
+    );
+    println!("  --pretty                  Indent JSON output (not with jsonl)");
+    println!("  --app <name>              Only records from this app (case-insensitive)");
+    println!("  --iso                     Add epoch_iso/closed_epoch_iso local datetimes");
+    println!("  --app-summary             Group by app: count and {{time, summary}} list");
+    println!("  --since-last              Only records newer than the previous --since-last run");
+    println!("  --reset-cursor            Forget the --since-last position before exporting");
+    println!("  --count-only              Print only the number of matching records");
+    println!(
+        "  --fields a,b,c            Only these fields, in this order (e.g. time,app_name,summary)"
+    );
+    println!("  --order asc|desc          Oldest or newest (default) first");
+    println!(
+        "  --merge-bodies            Collapse consecutive same app+summary records (count, earliest, latest)"
+    );
+    println!("  --output <file>           Write to a file instead of stdout (-o)");
+    println!("  --gzip                    Gzip-compress the output (to a file or a pipe)");
+    println!("  --app-filter-file <file>  Leave out the apps listed in the file (# comments)");
 }
 
 fn handle_logger(args: Vec<String>) -> Result<(), String> {
+    const USAGE: &str = "usage: notilog logger run | logger --daemon | logger status | logger stop | logger --once | logger --replay <file> [--verbose | --log-level error|warn|debug]";
+    let mut args = args;
+    // Forwarded to the background process by `--daemon`.
+    let mut level_args = Vec::new();
+    if let Some(index) = args.iter().position(|arg| arg == "--verbose") {
+        level_args.push(args.remove(index));
+        let _ = LOG_LEVEL.set(LogLevel::Debug);
+    } else if let Some(index) = args.iter().position(|arg| arg == "--log-level") {
+        let level = args
+            .get(index + 1)
+            .and_then(|value| LogLevel::parse(value))
+            .ok_or_else(|| String::from(USAGE))?;
+        level_args.extend(args.drain(index..=index + 1));
+        let _ = LOG_LEVEL.set(level);
+    }
+
     match args.as_slice() {
         [cmd] if cmd == "run" => run_logger(),
-        _ => Err(String::from("usage: notilog logger run")),
+        [flag] if flag == "--daemon" => start_logger_daemon(&level_args),
+        [cmd] if cmd == "status" => print_logger_status(),
+        [cmd] if cmd == "stop" => stop_logger_daemon(),
+        [flag] if flag == "--once" => replay_monitor_output(BufReader::new(std::io::stdin())),
+        [flag, file] if flag == "--replay" => {
+            let file =
+                File::open(file).map_err(|error| format!("could not open {file}: {error}"))?;
+            replay_monitor_output(BufReader::new(file))
+        }
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+fn logger_pid_path() -> Result<PathBuf, String> {
+    Ok(log_path()?.with_file_name(LOGGER_PID_FILE))
+}
+
+/// PID from the pid file, if that process is still a running `notilog`.
+/// A PID reused by an unrelated process counts as not running.
+fn running_logger_pid(pid_path: &PathBuf) -> Option<u32> {
+    let pid = fs::read_to_string(pid_path)
+        .ok()?
+        .trim()
+        .parse::<u32>()
+        .ok()?;
+    let cmdline = fs::read(format!("/proc/{pid}/cmdline")).ok()?;
+    String::from_utf8_lossy(&cmdline)
+        .contains("notilog")
+        .then_some(pid)
+}
+
+/// Re-runs this binary as `logger run` in its own process group, detached
+/// from the terminal, and records its PID for `logger status`/`logger stop`.
+fn start_logger_daemon(level_args: &[String]) -> Result<(), String> {
+    let pid_path = logger_pid_path()?;
+    if let Some(pid) = running_logger_pid(&pid_path) {
+        return Err(format!("logger is already running (pid {pid})"));
+    }
+
+    let exe = env::current_exe()
+        .map_err(|error| format!("could not locate the notilog binary: {error}"))?;
+    let mut command = Command::new(exe);
+    if let Some(path) = LOG_FILE_OVERRIDE.get() {
+        command.arg("--log-file").arg(path);
+    }
+    let child = command
+        .args(["logger", "run"])
+        .args(level_args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .map_err(|error| format!("could not start logger: {error}"))?;
+
+    let pid = child.id();
+    fs::write(&pid_path, format!("{pid}\n"))
+        .map_err(|error| format!("could not write {}: {error}", pid_path.display()))?;
+    println!("logger started (pid {pid})");
+    Ok(())
+}
+
+fn print_logger_status() -> Result<(), String> {
+    let pid_path = logger_pid_path()?;
+    match running_logger_pid(&pid_path) {
+        Some(pid) => println!("logger is running (pid {pid})"),
+        None if pid_path.exists() => println!("logger is not running (stale pid file)"),
+        None => println!("logger is not running"),
+    }
+    Ok(())
+}
+
+/// Sends SIGTERM to the daemon; its handler also stops `busctl monitor`.
+fn stop_logger_daemon() -> Result<(), String> {
+    let pid_path = logger_pid_path()?;
+    let Some(pid) = running_logger_pid(&pid_path) else {
+        let _ = fs::remove_file(&pid_path);
+        return Err(String::from("logger is not running"));
+    };
+
+    let status = Command::new("kill")
+        .arg(pid.to_string())
+        .status()
+        .map_err(|error| format!("could not run kill: {error}"))?;
+    if !status.success() {
+        return Err(format!("could not stop logger (pid {pid})"));
+    }
+    let _ = fs::remove_file(&pid_path);
+    println!("logger stopped (pid {pid})");
+    Ok(())
+}
+
+fn log_diagnostic(level: LogLevel, message: impl FnOnce() -> String) {
+    if LOG_LEVEL.get().copied().unwrap_or(LogLevel::Error) >= level {
+        let prefix = match level {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warning",
+            LogLevel::Debug => "debug",
+        };
+        eprintln!("{prefix}: {}", message());
     }
 }
 
 fn handle_mark_user(args: Vec<String>) -> Result<(), String> {
+    if let [flag] = args.as_slice()
+        && flag == "--all-expired"
+    {
+        return mark_all_expired_as_user();
+    }
+
     let target_event = match args.as_slice() {
         [flag, value] if flag == "--event" => Some(value.clone()),
         [flag, value] if flag == "--id" => {
@@ -144,15 +666,15 @@ fn handle_mark_user(args: Vec<String>) -> Result<(), String> {
         }
         _ => {
             return Err(String::from(
-                "usage: notilog mark-user --event <uid> (or --id <id>)",
+                "usage: notilog mark-user --event <uid> (or --id <id>, or --all-expired)",
             ));
         }
     };
 
     let path = log_path()?;
-    let max_notification_length = max_notification_length();
-    let records = read_records(&path)?;
-    let merged = aggregate_records(&records);
+    let retention = retention();
+    let store = LogStore::open(&path)?;
+    let merged = store.merged();
 
     let current = if let Some(event_marker) = target_event {
         if let Some(id_text) = event_marker.strip_prefix("id:") {
@@ -191,7 +713,7 @@ fn handle_mark_user(args: Vec<String>) -> Result<(), String> {
         "closed_hhmm": current.closed_hhmm.clone(),
     });
 
-    append_payload(&path, &payload, max_notification_length)?;
+    append_payload(&path, &payload, &retention)?;
 
     println!(
         "updated event {} close reason to dismissed-by-user",
@@ -200,183 +722,1367 @@ fn handle_mark_user(args: Vec<String>) -> Result<(), String> {
     Ok(())
 }
 
-fn handle_tail(args: Vec<String>) -> Result<(), String> {
-    let mut count = 20usize;
-    let mut iter = args.iter();
-    while let Some(arg) = iter.next() {
-        if arg == "--n" {
-            let Some(value) = iter.next() else {
-                return Err(String::from("usage: notilog tail [--n N]"));
-            };
-            count = value
-                .parse::<usize>()
-                .map_err(|_| String::from("--n expects a positive integer"))?;
-        } else {
-            return Err(String::from("usage: notilog tail [--n N]"));
-        }
-    }
-
+/// Marks every auto-dismissed (expired) notification as dismissed-by-user,
+/// appending all close records before pruning once.
+fn mark_all_expired_as_user() -> Result<(), String> {
     let path = log_path()?;
-    let records = read_records(&path)?;
-    let len = records.len();
-    let start = len.saturating_sub(count);
+    let retention = retention();
+    let store = LogStore::open(&path)?;
+    let updates = store
+        .merged_where(|record| record.close_reason_code == Some(1))
+        .into_iter()
+        .map(|current| {
+            let mut update = LogRecord::empty(current.id);
+            update.event_uid = current.event_uid.clone();
+            update.close_reason_code = Some(2);
+            update.close_reason = Some(String::from("dismissed-by-user"));
+            update.closed_epoch = current.closed_epoch;
+            update.closed_hhmm = current.closed_hhmm.clone();
+            update
+        })
+        .collect::<Vec<_>>();
 
-    for record in &records[start..] {
-        let id = record.id;
-        let hhmm = record
-            .hhmm
-            .as_deref()
-            .or(record.closed_hhmm.as_deref())
-            .unwrap_or("--:--");
-        let summary = record.summary.as_deref().unwrap_or("(no summary)");
-        let suffix = record
-            .close_reason
-            .as_deref()
-            .map(|reason| format!(" [closed:{reason}]"))
-            .unwrap_or_default();
-        println!("#{id} {hhmm} {summary}{suffix}");
+    append_records(&path, &updates)?;
+    if !updates.is_empty() {
+        prune_to_max_notifications(&path, &retention)?;
     }
-
+    println!(
+        "updated {} notification(s) to dismissed-by-user",
+        updates.len()
+    );
     Ok(())
 }
 
-fn handle_export() -> Result<(), String> {
+fn handle_unmark(args: Vec<String>) -> Result<(), String> {
+    let event_uid = match args.as_slice() {
+        [flag, value] if flag == "--event" => value,
+        _ => return Err(String::from("usage: notilog unmark --event <uid>")),
+    };
+
     let path = log_path()?;
-    let records = read_records(&path)?;
-    let merged = aggregate_records(&records);
+    let retention = retention();
+    let store = LogStore::open(&path)?;
+
+    let Some(current) = store
+        .merged()
+        .iter()
+        .find(|record| record.event_uid.as_deref() == Some(event_uid.as_str()))
+    else {
+        return Err(String::from("target notification not found in log"));
+    };
 
-    let payload = merged
-        .into_iter()
-        .map(|record| record_to_json(&record))
-        .collect::<Vec<_>>();
+    if current.close_reason_code != Some(2) {
+        return Err(format!(
+            "notification is not dismissed-by-user (current reason: {})",
+            current.close_reason.as_deref().unwrap_or("unknown")
+        ));
+    }
+
+    let payload = json!({
+        "event_uid": current.event_uid.clone(),
+        "id": current.id,
+        "close_reason_code": 1,
+        "close_reason": close_reason_label(1),
+        "closed_epoch": current.closed_epoch,
+        "closed_hhmm": current.closed_hhmm.clone(),
+    });
+
+    append_payload(&path, &payload, &retention)?;
 
     println!(
-        "{}",
-        serde_json::to_string(&payload)
-            .map_err(|error| format!("could not encode export payload: {error}"))?
+        "updated event {} close reason to expired",
+        current.event_uid.as_deref().unwrap_or("<unknown-event>")
     );
     Ok(())
 }
 
-fn handle_stats() -> Result<(), String> {
-    let path = log_path()?;
-    let records = read_records(&path)?;
-    println!("path: {}", path.display());
-    println!("records: {}", records.len());
-    Ok(())
-}
+fn handle_tail(args: Vec<String>) -> Result<(), String> {
+    const USAGE: &str = "usage: notilog tail [--n N] [--app <name>] [--reason <code|label>] [--follow] [--format text|json]";
+    let mut count = 20usize;
+    let mut filter = TailFilter::default();
+    let mut follow = false;
+    let mut as_json = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--n" => {
+                let value = iter.next().ok_or_else(|| String::from(USAGE))?;
+                count = value
+                    .parse::<usize>()
+                    .map_err(|_| String::from("--n expects a positive integer"))?;
+            }
+            "--app" => {
+                filter.app = Some(iter.next().ok_or_else(|| String::from(USAGE))?.clone());
+            }
+            "--reason" => {
+                let value = iter.next().ok_or_else(|| String::from(USAGE))?;
+                filter.reason_code = Some(parse_close_reason(value)?);
+            }
+            "--follow" | "-f" => follow = true,
+            "--format" => {
+                as_json = match iter.next().map(String::as_str) {
+                    Some("text") => false,
+                    Some("json") => true,
+                    _ => return Err(String::from(USAGE)),
+                };
+            }
+            // Shorthand for `--format json`.
+            "--json" => as_json = true,
+            _ => return Err(String::from(USAGE)),
+        }
+    }
 
-fn handle_query(args: Vec<String>) -> Result<(), String> {
-    let id = parse_single_u32_flag(&args, "--id")?;
+    if follow && stdin_log().is_some() {
+        return Err(String::from("--follow cannot be combined with --stdin"));
+    }
     let path = log_path()?;
-    let records = read_records(&path)?;
-    let merged = aggregate_records(&records);
+    let records = read_last_records(&path, count, &filter)?;
 
-    let found = merged.into_iter().find(|record| record.id == id);
-    if let Some(record) = found {
-        println!(
-            "{}",
-            serde_json::to_string(&record_to_json(&record))
-                .map_err(|error| format!("could not encode query result: {error}"))?
-        );
-    } else {
-        println!("null");
+    for record in &records {
+        print_tail_record(record, as_json)?;
     }
 
+    if follow {
+        follow_log(&path, &filter, as_json)?;
+    }
     Ok(())
 }
 
-fn handle_lookup(args: Vec<String>) -> Result<(), String> {
-    let ids_arg = parse_single_string_flag(&args, "--ids")?;
-    let wanted_ids: HashSet<u32> = ids_arg
-        .split(',')
-        .map(str::trim)
-        .filter(|part| !part.is_empty())
-        .map(|part| {
-            part.parse::<u32>()
-                .map_err(|_| format!("invalid id '{part}' in --ids"))
-        })
-        .collect::<Result<HashSet<_>, _>>()?;
-
-    let path = log_path()?;
-    let records = read_records(&path)?;
-    let merged = aggregate_records(&records);
+fn print_tail_record(record: &LogRecord, as_json: bool) -> Result<(), String> {
+    if as_json {
+        let line = serde_json::to_string(&record_to_json(record))
+            .map_err(|error| format!("could not encode record: {error}"))?;
+        println!("{line}");
+    } else {
+        println!("{}", tail_line(record));
+    }
+    // Followers are usually piped into another program; don't sit in a buffer.
+    std::io::stdout()
+        .flush()
+        .map_err(|error| format!("could not write to stdout: {error}"))
+}
 
-    let mut out = serde_json::Map::new();
-    for record in merged {
-        if !wanted_ids.contains(&record.id) {
+/// Prints records as they are appended, until interrupted. Close updates are
+/// their own lines, so they show up too (with `close_reason` set). Only the
+/// bytes past the last read are read; when the file shrinks or is replaced
+/// (the logger rewrites it when pruning), it is read again from the start.
+fn follow_log(path: &PathBuf, filter: &TailFilter, as_json: bool) -> Result<(), String> {
+    let mut position = fs::metadata(path)
+        .map(|metadata| (metadata.ino(), metadata.len()))
+        .ok();
+    loop {
+        thread::sleep(FOLLOW_POLL_INTERVAL);
+        let Ok(metadata) = fs::metadata(path) else {
+            position = None;
+            continue;
+        };
+        let offset = match position {
+            Some((inode, offset)) if inode == metadata.ino() && offset <= metadata.len() => offset,
+            _ => 0,
+        };
+        if offset == metadata.len() {
+            position = Some((metadata.ino(), offset));
             continue;
         }
-        if let Some(hhmm) = record.hhmm {
-            let key = record.id.to_string();
-            out.entry(key).or_insert(Value::String(hhmm));
+
+        let (lines, read) = read_appended_lines(path, offset)?;
+        for line in &lines {
+            if let Some(record) = parse_record_line(line).filter(|record| filter.matches(record)) {
+                print_tail_record(&record, as_json)?;
+            }
         }
+        position = Some((metadata.ino(), offset + read));
     }
+}
 
-    println!(
-        "{}",
-        serde_json::to_string(&Value::Object(out))
-            .map_err(|error| format!("could not encode lookup result: {error}"))?
-    );
+/// Complete lines written after byte `offset`, and how many bytes they span;
+/// a line still being written is left for the next read.
+fn read_appended_lines(path: &PathBuf, offset: u64) -> Result<(Vec<String>, u64), String> {
+    let read_error = |error: std::io::Error| format!("could not read {}: {error}", path.display());
+    let mut file =
+        File::open(path).map_err(|error| format!("could not open {}: {error}", path.display()))?;
+    file.seek(SeekFrom::Start(offset)).map_err(read_error)?;
+    let mut appended = Vec::new();
+    file.read_to_end(&mut appended).map_err(read_error)?;
+
+    let complete = appended
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |index| index + 1);
+    let lines = String::from_utf8_lossy(&appended[..complete])
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect();
+    Ok((lines, complete as u64))
+}
 
-    Ok(())
+/// Accepts a numeric close reason code or one of its labels (`expired`, ...).
+fn parse_close_reason(value: &str) -> Result<u32, String> {
+    if let Ok(code) = value.parse::<u32>() {
+        return Ok(code);
+    }
+    (1..=4)
+        .find(|code| close_reason_label(*code).eq_ignore_ascii_case(value))
+        .ok_or_else(|| {
+            format!(
+                "--reason expects a code or one of expired, dismissed-by-user, closed-by-call, undefined; got `{value}`"
+            )
+        })
 }
 
-fn handle_prune(args: Vec<String>) -> Result<(), String> {
-    let days = parse_single_u64_flag(&args, "--days")?;
+/// One-line rendering of a raw record shared by `tail` and `replay`.
+fn tail_line(record: &LogRecord) -> String {
+    let id = record.id;
+    let hhmm = record
+        .hhmm
+        .as_deref()
+        .or(record.closed_hhmm.as_deref())
+        .unwrap_or("--:--");
+    let summary = record.summary.as_deref().unwrap_or("(no summary)");
+    let suffix = record
+        .close_reason
+        .as_deref()
+        .map(|reason| format!(" [closed:{reason}]"))
+        .unwrap_or_default();
+    format!("#{id} {hhmm} {summary}{suffix}")
+}
+
+/// Re-prints raw records in epoch order, sleeping for the real gap between
+/// them divided by `--speed`. Read-only; meant for demos and screenshots.
+fn handle_replay(args: Vec<String>) -> Result<(), String> {
+    let speed = match args.as_slice() {
+        [] => 1.0,
+        [flag, value] if flag == "--speed" => value
+            .parse::<f64>()
+            .ok()
+            .filter(|speed| speed.is_finite() && *speed > 0.0)
+            .ok_or_else(|| String::from("--speed expects a positive number"))?,
+        _ => return Err(String::from("usage: notilog replay [--speed X]")),
+    };
+
     let path = log_path()?;
     let mut records = read_records(&path)?;
+    // Records without any epoch cannot be placed on the timeline.
+    records.retain(|record| event_epoch(record).is_some());
+    records.sort_by_key(event_epoch);
+
+    let mut previous_epoch = None;
+    for record in &records {
+        let epoch = event_epoch(record).unwrap_or_default();
+        if let Some(previous) = previous_epoch {
+            let gap = epoch.saturating_sub(previous).max(0) as f64;
+            thread::sleep(Duration::from_secs_f64(gap / speed));
+        }
+        previous_epoch = Some(epoch);
+        println!("{}", tail_line(record));
+    }
 
-    let now = now_epoch();
-    let cutoff = now.saturating_sub((days as i64).saturating_mul(24 * 60 * 60));
-
-    let before = records.len();
-    records.retain(|record| match event_epoch(record) {
-        Some(epoch) => epoch >= cutoff,
-        None => true,
-    });
+    Ok(())
+}
+
+fn handle_export(args: Vec<String>) -> Result<(), String> {
+    let options = ExportOptions::parse(&args)?;
+    // The cursor belongs to the configured log, not to piped input.
+    if (options.since_last || options.reset_cursor) && stdin_log().is_some() {
+        return Err(String::from(
+            "--since-last and --reset-cursor cannot be combined with --stdin",
+        ));
+    }
+    let path = log_path()?;
+    if let Some(output) = &options.output
+        && stdin_log().is_none()
+        && fs::canonicalize(output).ok() == fs::canonicalize(&path).ok()
+        && output.exists()
+    {
+        return Err(format!(
+            "--output would overwrite the log itself ({})",
+            path.display()
+        ));
+    }
+    let cursor_path = path.with_file_name(EXPORT_CURSOR_FILE);
+    if options.reset_cursor && cursor_path.exists() {
+        fs::remove_file(&cursor_path)
+            .map_err(|error| format!("could not remove {}: {error}", cursor_path.display()))?;
+    }
+    let cursor = if options.since_last {
+        read_export_cursor(&cursor_path)?
+    } else {
+        None
+    };
+
+    let store = LogStore::open(&path)?;
+    let mut selected = store.merged_where(|record| {
+        options
+            .app
+            .as_deref()
+            .is_none_or(|app| record_matches_app(record, app))
+            && !options
+                .excluded_apps
+                .iter()
+                .any(|app| record_matches_app(record, app))
+            && cursor.as_ref().is_none_or(|cursor| cursor.is_new(record))
+    });
+    // Counting is a peek: it skips encoding and leaves the --since-last cursor.
+    if options.count_only {
+        let count = if options.merge_bodies {
+            merge_repeated(&selected).len()
+        } else {
+            selected.len()
+        };
+        println!("{count}");
+        return Ok(());
+    }
+    // Runs are merged newest first (see `merge_repeated`), then flipped.
+    let mut merged_runs = if options.merge_bodies {
+        merge_repeated(&selected)
+    } else {
+        Vec::new()
+    };
+    if options.oldest_first {
+        selected.reverse();
+        merged_runs.reverse();
+    }
+    let timezone = app_config::load_or_create().timezone;
+    let time_format = if options.iso {
+        TimeFormat::Iso(timezone)
+    } else {
+        TimeFormat::Logged
+    };
+    let payload = if options.app_summary {
+        app_summary_json(&selected, time_format)
+    } else if options.merge_bodies {
+        merged_runs
+            .into_iter()
+            .map(|(latest, earliest, count)| {
+                let mut value = record_to_json(latest);
+                value["count"] = json!(count);
+                value["latest"] = json!(record_time(latest, time_format));
+                value["earliest"] = json!(record_time(earliest, time_format));
+                value
+            })
+            .collect()
+    } else {
+        selected
+            .iter()
+            .map(|record| {
+                let mut value = record_to_json(record);
+                if options.iso || options.fields.is_some() {
+                    value["epoch_iso"] =
+                        json!(record.epoch.and_then(|epoch| epoch_to_iso(epoch, timezone)));
+                    value["closed_epoch_iso"] = json!(
+                        record
+                            .closed_epoch
+                            .and_then(|epoch| epoch_to_iso(epoch, timezone))
+                    );
+                }
+                if options.fields.is_some() {
+                    value["time"] = json!(record_time(record, time_format));
+                }
+                value
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let text = match (options.format, &options.fields) {
+        (ExportFormat::Json, Some(fields)) => {
+            let objects = payload
+                .iter()
+                .map(|value| encode_fields(value, fields, options.pretty))
+                .collect::<serde_json::Result<Vec<_>>>()
+                .map_err(|error| format!("could not encode export payload: {error}"))?;
+            format!("{}\n", join_json_array(&objects, options.pretty))
+        }
+        (ExportFormat::Json, None) => {
+            let encoded = encode_json(&Value::Array(payload), options.pretty)
+                .map_err(|error| format!("could not encode export payload: {error}"))?;
+            format!("{encoded}\n")
+        }
+        (ExportFormat::JsonLines, fields) => {
+            let mut lines = String::new();
+            for value in &payload {
+                let encoded = match fields {
+                    Some(fields) => encode_fields(value, fields, false),
+                    None => serde_json::to_string(value),
+                }
+                .map_err(|error| format!("could not encode export record: {error}"))?;
+                lines.push_str(&encoded);
+                lines.push('\n');
+            }
+            lines
+        }
+        (ExportFormat::Markdown, _) => markdown_table(&selected, time_format),
+    };
+    write_export(&text, options.output.as_deref(), options.gzip)?;
+
+    if options.since_last
+        && let Some(next) = ExportCursor::advance(cursor, &selected)
+    {
+        fs::write(&cursor_path, next.to_text())
+            .map_err(|error| format!("could not write {}: {error}", cursor_path.display()))?;
+    }
+    Ok(())
+}
+
+/// Writes the export to `output` (or stdout), gzip-compressed when asked.
+fn write_export(text: &str, output: Option<&Path>, gzip: bool) -> Result<(), String> {
+    let target = output.map_or_else(|| String::from("stdout"), |path| path.display().to_string());
+    let sink: Box<dyn Write> = match output {
+        Some(path) => Box::new(
+            File::create(path)
+                .map_err(|error| format!("could not create {}: {error}", path.display()))?,
+        ),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let result = if gzip {
+        let mut encoder = GzEncoder::new(sink, Compression::default());
+        encoder
+            .write_all(text.as_bytes())
+            .and_then(|()| encoder.finish())
+            .and_then(|mut sink| sink.flush())
+    } else {
+        let mut sink = sink;
+        sink.write_all(text.as_bytes()).and_then(|()| sink.flush())
+    };
+    result.map_err(|error| format!("could not write to {target}: {error}"))
+}
+
+/// Collapses runs of consecutive records sharing `app_name` and `summary`
+/// into `(latest, earliest, count)`; input and output are newest first.
+fn merge_repeated<'a>(records: &[&'a LogRecord]) -> Vec<(&'a LogRecord, &'a LogRecord, usize)> {
+    let mut groups: Vec<(&LogRecord, &LogRecord, usize)> = Vec::new();
+    for record in records {
+        match groups.last_mut() {
+            Some((latest, earliest, count))
+                if latest.app_name == record.app_name && latest.summary == record.summary =>
+            {
+                *earliest = record;
+                *count += 1;
+            }
+            _ => groups.push((record, record, 1)),
+        }
+    }
+    groups
+}
+
+/// GitHub-flavored table with Time, App, Summary, and Reason columns; bodies
+/// are left out so each notification stays on one row.
+fn markdown_table(records: &[&LogRecord], time_format: TimeFormat) -> String {
+    let mut table = String::from("| Time | App | Summary | Reason |\n| --- | --- | --- | --- |\n");
+    for record in records {
+        let cells = [
+            record_time(record, time_format).unwrap_or_default(),
+            record.app_name.clone().unwrap_or_default(),
+            record.summary.clone().unwrap_or_default(),
+            record
+                .close_reason
+                .clone()
+                .unwrap_or_else(|| String::from("open")),
+        ];
+        let row = cells
+            .iter()
+            .map(|cell| markdown_cell(cell))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        table.push_str(&format!("| {row} |\n"));
+    }
+    table
+}
+
+/// Escapes `|` and folds line breaks so the text cannot break the row.
+fn markdown_cell(text: &str) -> String {
+    text.split(['\n', '\r'])
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.trim().replace('|', "\\|"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// One object per app (`app`, `count`, `notifications: [{time, summary}]`),
+/// busiest app first; records without an app are grouped under `null`.
+fn app_summary_json(records: &[&LogRecord], time_format: TimeFormat) -> Vec<Value> {
+    let mut groups: Vec<(Option<&str>, Vec<Value>)> = Vec::new();
+    for record in records {
+        let app_name = record.app_name.as_deref();
+        let entry = json!({ "time": record_time(record, time_format), "summary": record.summary });
+        match groups.iter_mut().find(|(name, _)| *name == app_name) {
+            Some((_, entries)) => entries.push(entry),
+            None => groups.push((app_name, vec![entry])),
+        }
+    }
+
+    // Stable sort keeps first-seen (newest) order among equally busy apps.
+    groups.sort_by_key(|(_, entries)| std::cmp::Reverse(entries.len()));
+    groups
+        .into_iter()
+        .map(|(app_name, entries)| {
+            json!({
+                "app": app_name,
+                "count": entries.len(),
+                "notifications": entries,
+            })
+        })
+        .collect()
+}
+
+fn record_time(record: &LogRecord, time_format: TimeFormat) -> Option<String> {
+    match time_format {
+        TimeFormat::Iso(timezone) => {
+            event_epoch(record).and_then(|epoch| epoch_to_iso(epoch, timezone))
+        }
+        TimeFormat::Logged => record.hhmm.clone().or_else(|| record.closed_hhmm.clone()),
+    }
+}
+
+/// RFC 3339 timestamp in `timezone` (or the local zone), e.g.
+/// `2026-10-15T12:00:00+02:00`.
+fn epoch_to_iso(epoch: i64, timezone: Option<Tz>) -> Option<String> {
+    let time = DateTime::from_timestamp(epoch, 0)?;
+    Some(match timezone {
+        Some(timezone) => time.with_timezone(&timezone).to_rfc3339(),
+        None => time.with_timezone(&Local).to_rfc3339(),
+    })
+}
+
+/// Where `--since-last` stopped: the newest exported epoch plus the event
+/// uids exported at that second, so a notification logged later in the same
+/// second is still picked up by the next run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ExportCursor {
+    epoch: i64,
+    event_uids: HashSet<String>,
+}
+
+impl ExportCursor {
+    fn is_new(&self, record: &LogRecord) -> bool {
+        event_epoch(record).is_some_and(|epoch| {
+            epoch > self.epoch
+                || (epoch == self.epoch
+                    && record
+                        .event_uid
+                        .as_ref()
+                        .is_none_or(|event_uid| !self.event_uids.contains(event_uid)))
+        })
+    }
+
+    /// The cursor after exporting `exported`; `None` when nothing dated was
+    /// exported and the old cursor stays.
+    fn advance(previous: Option<Self>, exported: &[&LogRecord]) -> Option<Self> {
+        let epoch = exported
+            .iter()
+            .filter_map(|record| event_epoch(record))
+            .max()?;
+        let mut event_uids = previous
+            .filter(|previous| previous.epoch == epoch)
+            .map(|previous| previous.event_uids)
+            .unwrap_or_default();
+        event_uids.extend(
+            exported
+                .iter()
+                .filter(|record| event_epoch(record) == Some(epoch))
+                .filter_map(|record| record.event_uid.clone()),
+        );
+        Some(Self { epoch, event_uids })
+    }
+
+    /// The epoch on the first line, then one event uid per line.
+    fn to_text(&self) -> String {
+        let mut event_uids = self.event_uids.iter().collect::<Vec<_>>();
+        event_uids.sort();
+        let mut text = format!("{}\n", self.epoch);
+        for event_uid in event_uids {
+            text.push_str(event_uid);
+            text.push('\n');
+        }
+        text
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        Some(Self {
+            epoch: lines.next()?.parse().ok()?,
+            event_uids: lines.map(ToString::to_string).collect(),
+        })
+    }
+}
+
+fn read_export_cursor(path: &PathBuf) -> Result<Option<ExportCursor>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("could not read {}: {error}", path.display()))?;
+    ExportCursor::parse(&text).map(Some).ok_or_else(|| {
+        format!(
+            "invalid export cursor in {} (use --reset-cursor)",
+            path.display()
+        )
+    })
+}
+
+fn handle_stats(args: Vec<String>) -> Result<(), String> {
+    let json = match args.as_slice() {
+        [] => false,
+        [flag] if flag == "--json" => true,
+        _ => return Err(String::from("usage: notilog stats [--json]")),
+    };
+
+    let path = log_path()?;
+    if json {
+        let payload = stats_json(&path)?;
+        println!(
+            "{}",
+            serde_json::to_string(&payload)
+                .map_err(|error| format!("could not encode stats: {error}"))?
+        );
+        return Ok(());
+    }
+
+    let records = count_record_lines(&path)?;
+    println!("path: {}", log_display(&path));
+    println!("records: {records}");
+    Ok(())
+}
+
+fn handle_watch(args: Vec<String>) -> Result<(), String> {
+    let interval = match args.as_slice() {
+        [] => 5,
+        [flag, value] if flag == "--interval" => value
+            .parse::<u64>()
+            .ok()
+            .filter(|seconds| *seconds > 0)
+            .ok_or_else(|| String::from("--interval expects a positive integer"))?,
+        _ => return Err(String::from("usage: notilog watch [--interval N]")),
+    };
+
+    let path = log_path()?;
+    let mut stdout = std::io::stdout();
+    loop {
+        let body = match stats_json(&path) {
+            Ok(payload) => serde_json::to_string_pretty(&payload)
+                .map_err(|error| format!("could not encode stats: {error}"))?,
+            Err(error) => format!("error: {error}"),
+        };
+
+        // Clear screen and move home; no terminal modes change, so Ctrl-C
+        // leaves the shell as it was.
+        write!(
+            stdout,
+            "\x1b[2J\x1b[Hnotilog watch (every {interval}s, Ctrl-C to stop)\n\n{body}\n"
+        )
+        .and_then(|()| stdout.flush())
+        .map_err(|error| format!("could not write to stdout: {error}"))?;
+
+        thread::sleep(Duration::from_secs(interval));
+    }
+}
+
+fn stats_json(path: &PathBuf) -> Result<Value, String> {
+    let store = LogStore::open(path)?;
+    let records = store.all();
+    let merged = store.merged();
+
+    let mut close_reasons = serde_json::Map::new();
+    let mut open = 0usize;
+    for record in merged {
+        let Some(code) = record.close_reason_code else {
+            open += 1;
+            continue;
+        };
+        let reason = record
+            .close_reason
+            .clone()
+            .unwrap_or_else(|| close_reason_label(code).to_string());
+        let count = close_reasons
+            .get(&reason)
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        close_reasons.insert(reason, json!(count + 1));
+    }
+
+    let epochs = records
+        .iter()
+        .flat_map(|record| [record.epoch, record.closed_epoch])
+        .flatten();
+    let earliest_epoch = epochs.clone().min();
+    let latest_epoch = epochs.max();
+    let size_bytes = match stdin_log() {
+        Some(bytes) => bytes.len() as u64,
+        None => fs::metadata(path).map(|meta| meta.len()).unwrap_or(0),
+    };
+
+    Ok(json!({
+        "path": log_display(path),
+        "records": records.len(),
+        "notifications": merged.len(),
+        "close_reasons": close_reasons,
+        "open": open,
+        "earliest_epoch": earliest_epoch,
+        "latest_epoch": latest_epoch,
+        "size_bytes": size_bytes,
+    }))
+}
+
+fn handle_query(args: Vec<String>) -> Result<(), String> {
+    const USAGE: &str = "usage: notilog query --id <id> | --event <uid> | --summary <text> | --body-regex <pattern> [--limit N] [--pretty] [--count-only]";
+    let mut id = None;
+    let mut event_uid = None;
+    let mut summary = None;
+    let mut body_regex = None;
+    let mut limit = None;
+    let mut pretty = false;
+    let mut count_only = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--pretty" => pretty = true,
+            "--count-only" => count_only = true,
+            "--id" => {
+                let value = iter.next().ok_or_else(|| String::from(USAGE))?;
+                id = Some(
+                    value
+                        .parse::<u32>()
+                        .map_err(|_| String::from("--id expects an integer"))?,
+                );
+            }
+            "--event" => {
+                let value = iter.next().ok_or_else(|| String::from(USAGE))?;
+                event_uid = Some(value.clone());
+            }
+            "--summary" => {
+                let value = iter.next().ok_or_else(|| String::from(USAGE))?;
+                summary = Some(value.to_lowercase());
+            }
+            "--body-regex" => {
+                let value = iter.next().ok_or_else(|| String::from(USAGE))?;
+                body_regex = Some(
+                    Regex::new(value)
+                        .map_err(|error| format!("invalid --body-regex pattern: {error}"))?,
+                );
+            }
+            "--limit" => {
+                let value = iter.next().ok_or_else(|| String::from(USAGE))?;
+                limit = Some(
+                    value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|limit| *limit > 0)
+                        .ok_or_else(|| String::from("--limit expects a positive integer"))?,
+                );
+            }
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+
+    let path = log_path()?;
+    let store = LogStore::open(&path)?;
+    let merged = store.merged();
+
+    // --summary and --body-regex may be combined; a record must match both.
+    let search = summary.is_some() || body_regex.is_some();
+    let matches = match (id, event_uid, search) {
+        (Some(id), None, false) => merged
+            .iter()
+            .filter(|record| record.id == id)
+            .take(1)
+            .collect::<Vec<_>>(),
+        (None, Some(event_uid), false) => merged
+            .iter()
+            .filter(|record| record.event_uid.as_deref() == Some(event_uid.as_str()))
+            .take(1)
+            .collect(),
+        (None, None, true) => merged
+            .iter()
+            .filter(|record| {
+                summary.as_deref().is_none_or(|needle| {
+                    record
+                        .summary
+                        .as_deref()
+                        .is_some_and(|summary| summary.to_lowercase().contains(needle))
+                })
+            })
+            .filter(|record| {
+                body_regex.as_ref().is_none_or(|pattern| {
+                    record
+                        .body
+                        .as_deref()
+                        .is_some_and(|body| pattern.is_match(body))
+                })
+            })
+            .take(limit.unwrap_or(usize::MAX))
+            .collect(),
+        _ => return Err(String::from(USAGE)),
+    };
+
+    if count_only {
+        println!("{}", matches.len());
+        return Ok(());
+    }
+    let payload = if search {
+        Value::Array(matches.into_iter().map(record_to_json).collect())
+    } else {
+        matches
+            .first()
+            .map(|record| record_to_json(record))
+            .unwrap_or(Value::Null)
+    };
+
+    println!(
+        "{}",
+        encode_json(&payload, pretty)
+            .map_err(|error| format!("could not encode query result: {error}"))?
+    );
+    Ok(())
+}
+
+fn handle_last(args: Vec<String>) -> Result<(), String> {
+    let line = match args.as_slice() {
+        [] => false,
+        [flag, format] if flag == "--format" && format == "json" => false,
+        [flag, format] if flag == "--format" && format == "line" => true,
+        _ => return Err(String::from("usage: notilog last [--format json|line]")),
+    };
+
+    let path = log_path()?;
+    let store = LogStore::open(&path)?;
+    let latest = latest_record(store.all());
+
+    if line {
+        if let Some(record) = latest {
+            let time =
+                record_time(&record, TimeFormat::Logged).unwrap_or_else(|| String::from("--:--"));
+            let summary = record.summary.as_deref().unwrap_or("(no summary)");
+            println!("{time} {summary}");
+        }
+        return Ok(());
+    }
+    let payload = latest
+        .map(|record| record_to_json(&record))
+        .unwrap_or(Value::Null);
+    println!(
+        "{}",
+        serde_json::to_string(&payload)
+            .map_err(|error| format!("could not encode record: {error}"))?
+    );
+    Ok(())
+}
+
+/// The first record `aggregate_records` would return, without merging and
+/// sorting every notification: only the newest one's records are merged.
+fn latest_record(records: &[LogRecord]) -> Option<LogRecord> {
+    let keys = record_event_keys(records);
+    let (newest_index, _) = records
+        .iter()
+        .enumerate()
+        .max_by_key(|(index, record)| record_rank(record, *index))?;
+    let key = &keys[newest_index];
+
+    let mut merged = LogRecord::empty(records[newest_index].id);
+    merged.event_uid = Some(key.clone());
+    for (index, record) in records.iter().enumerate() {
+        if &keys[index] == key {
+            merged.merge_from(record);
+        }
+    }
+    Some(merged)
+}
+
+fn handle_lookup(args: Vec<String>) -> Result<(), String> {
+    let ids_arg = parse_single_string_flag(&args, "--ids")?;
+    let wanted_ids: HashSet<u32> = ids_arg
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            part.parse::<u32>()
+                .map_err(|_| format!("invalid id '{part}' in --ids"))
+        })
+        .collect::<Result<HashSet<_>, _>>()?;
+
+    let path = log_path()?;
+    let store = LogStore::open(&path)?;
+
+    let mut out = serde_json::Map::new();
+    for record in store.merged_where(|record| wanted_ids.contains(&record.id)) {
+        if let Some(hhmm) = &record.hhmm {
+            let key = record.id.to_string();
+            out.entry(key).or_insert(Value::String(hhmm.clone()));
+        }
+    }
+
+    println!(
+        "{}",
+        serde_json::to_string(&Value::Object(out))
+            .map_err(|error| format!("could not encode lookup result: {error}"))?
+    );
+
+    Ok(())
+}
+
+fn handle_prune(args: Vec<String>) -> Result<(), String> {
+    let mut args = args;
+    let dry_run = match args.iter().position(|arg| arg == "--dry-run") {
+        Some(index) => {
+            args.remove(index);
+            true
+        }
+        None => false,
+    };
+    if args.iter().any(|arg| arg == "--days") && args.iter().any(|arg| arg == "--keep") {
+        return Err(String::from("--days and --keep cannot be used together"));
+    }
+    let keep = match args.first().map(String::as_str) {
+        Some("--keep") => {
+            let keep = parse_single_u64_flag(&args, "--keep")? as usize;
+            if keep == 0 {
+                return Err(String::from("--keep expects a positive integer"));
+            }
+            Some(keep)
+        }
+        _ => None,
+    };
+    let path = log_path()?;
+    let mut records = read_records(&path)?;
+    let before = records.len();
+    let original = dry_run.then(|| records.clone());
+
+    match keep {
+        Some(keep) => records = trim_records_to_latest_notifications(records, keep),
+        None => {
+            let days = parse_single_u64_flag(&args, "--days")?;
+            let now = now_epoch();
+            let cutoff = now.saturating_sub((days as i64).saturating_mul(24 * 60 * 60));
+            records.retain(|record| match event_epoch(record) {
+                Some(epoch) => epoch >= cutoff,
+                None => true,
+            });
+        }
+    }
 
-    write_records(&path, &records)?;
     let removed = before.saturating_sub(records.len());
+    if let Some(original) = original {
+        println!("dry run: {} not modified", path.display());
+        println!("removed: {removed}");
+        println!("remaining: {}", records.len());
+        for record in dropped_records(&original, &records) {
+            println!("  {}", tail_line(record));
+        }
+        return Ok(());
+    }
+
+    write_records(&path, &records)?;
     println!("removed: {removed}");
     println!("remaining: {}", records.len());
     Ok(())
 }
 
-fn run_logger() -> Result<(), String> {
+fn handle_purge_app(args: Vec<String>) -> Result<(), String> {
+    let [app] = args.as_slice() else {
+        return Err(String::from("usage: notilog purge-app <name>"));
+    };
+    let path = log_path()?;
+    let records = read_records(&path)?;
+    let before = records.len();
+    let (kept, notifications) = purge_app_records(records, app);
+
+    let removed = before - kept.len();
+    if removed > 0 {
+        write_records(&path, &kept)?;
+    }
+    println!("removed notifications: {notifications}");
+    println!("removed lines: {removed}");
+    println!("remaining: {}", kept.len());
+    Ok(())
+}
+
+/// Drops every record of notifications from `app` (case-insensitive),
+/// including their close records, which carry no app name of their own.
+/// Returns the kept records and how many notifications were dropped.
+fn purge_app_records(records: Vec<LogRecord>, app: &str) -> (Vec<LogRecord>, usize) {
+    let keys = record_event_keys(&records);
+    let dropped = records
+        .iter()
+        .zip(&keys)
+        .filter(|(record, _)| record_matches_app(record, app))
+        .map(|(_, key)| key.clone())
+        .collect::<HashSet<_>>();
+    let kept = records
+        .into_iter()
+        .zip(&keys)
+        .filter(|(_, key)| !dropped.contains(*key))
+        .map(|(record, _)| record)
+        .collect();
+    (kept, dropped.len())
+}
+
+/// Records of `original` missing from `kept`, in log order. Pruning only
+/// drops records, so identical lines are matched up by count.
+fn dropped_records<'a>(original: &'a [LogRecord], kept: &[LogRecord]) -> Vec<&'a LogRecord> {
+    let mut kept_counts: HashMap<String, usize> = HashMap::new();
+    for record in kept {
+        *kept_counts
+            .entry(record_to_json(record).to_string())
+            .or_default() += 1;
+    }
+    original
+        .iter()
+        .filter(
+            |record| match kept_counts.get_mut(&record_to_json(record).to_string()) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            },
+        )
+        .collect()
+}
+
+fn handle_import(args: Vec<String>) -> Result<(), String> {
+    let source = match args.as_slice() {
+        [file] => PathBuf::from(file),
+        _ => return Err(String::from("usage: notilog import <file>")),
+    };
+    if !source.exists() {
+        return Err(format!("{} does not exist", source.display()));
+    }
+
+    let path = log_path()?;
+    let retention = retention();
+    let existing = read_records(&path)?;
+    let known_events = existing
+        .iter()
+        .filter_map(|record| record.event_uid.clone())
+        .collect::<HashSet<_>>();
+
+    let (mut added, skipped): (Vec<LogRecord>, Vec<LogRecord>) = read_records(&source)?
+        .into_iter()
+        .partition(|record| match record.event_uid.as_deref() {
+            Some(event_uid) => !known_events.contains(event_uid),
+            None => true,
+        });
+
+    for (seq, record) in (next_seq(&existing)..).zip(added.iter_mut()) {
+        record.seq = Some(seq);
+    }
+    append_records(&path, &added)?;
+    prune_to_max_notifications(&path, &retention)?;
+
+    println!("added: {}", added.len());
+    println!("skipped: {}", skipped.len());
+    Ok(())
+}
+
+/// Combines whole logs, e.g. per-host backups, oldest notification first.
+/// Without `--output` the configured log is merged in first and replaced.
+fn handle_merge_logs(args: Vec<String>) -> Result<(), String> {
+    const USAGE: &str = "usage: notilog merge-logs <file>... [--output <file>]";
+    let mut sources = Vec::new();
+    let mut output = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--output" | "-o" => {
+                output = Some(PathBuf::from(
+                    iter.next().ok_or_else(|| String::from(USAGE))?,
+                ));
+            }
+            flag if flag.starts_with('-') => return Err(String::from(USAGE)),
+            file => sources.push(PathBuf::from(file)),
+        }
+    }
+    if sources.is_empty() {
+        return Err(String::from(USAGE));
+    }
+    if let Some(missing) = sources.iter().find(|source| !source.exists()) {
+        return Err(format!("{} does not exist", missing.display()));
+    }
+
+    let live_log = log_path()?;
+    let target = match output {
+        Some(path) => path,
+        None => {
+            sources.insert(0, live_log.clone());
+            live_log.clone()
+        }
+    };
+    let replaces_live_log = canonical_path(&target) == canonical_path(&live_log);
+    if replaces_live_log && let Some(pid) = running_logger_pid(&logger_pid_path()?) {
+        return Err(format!(
+            "the logger is running (pid {pid}) and would write over the merged log; stop it with `notilog logger stop` or write another file with --output"
+        ));
+    }
+    // A file named twice (or the configured log named again) is read once.
+    let mut seen = HashSet::new();
+    sources.retain(|source| seen.insert(canonical_path(source)));
+
+    let records = sources
+        .iter()
+        .map(read_records)
+        .collect::<Result<Vec<_>, _>>()?;
+    let (mut merged, skipped) = merge_log_sources(records);
+    // Each source numbered its own records; renumber in the merged order.
+    for (seq, record) in (0..).zip(merged.iter_mut()) {
+        record.seq = Some(seq);
+    }
+    write_records(&target, &merged)?;
+    if replaces_live_log {
+        prune_to_max_notifications(&target, &retention())?;
+    }
+
+    println!("sources: {}", sources.len());
+    println!("records: {}", merged.len());
+    println!("skipped: {skipped}");
+    Ok(())
+}
+
+/// `path` with symlinks and `..` resolved; a file that does not exist yet is
+/// resolved through its directory.
+fn canonical_path(path: &Path) -> PathBuf {
+    if let Ok(resolved) = fs::canonicalize(path) {
+        return resolved;
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    match (fs::canonicalize(parent), path.file_name()) {
+        (Ok(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Concatenates the sources, taking each `event_uid` (all of its records)
+/// from the first source that has it, as `import` does; records without one
+/// are all kept. Returns the records sorted by event and how many were
+/// skipped.
+fn merge_log_sources(sources: Vec<Vec<LogRecord>>) -> (Vec<LogRecord>, usize) {
+    let mut owners: HashMap<String, usize> = HashMap::new();
+    let mut merged = Vec::new();
+    let mut skipped = 0usize;
+    for (index, records) in sources.into_iter().enumerate() {
+        for record in records {
+            let keep = record
+                .event_uid
+                .clone()
+                .is_none_or(|event_uid| *owners.entry(event_uid).or_insert(index) == index);
+            if keep {
+                merged.push(record);
+            } else {
+                skipped += 1;
+            }
+        }
+    }
+    (sort_records_by_event(merged), skipped)
+}
+
+fn handle_config(args: Vec<String>) -> Result<(), String> {
+    let show = match args.as_slice() {
+        [cmd] if cmd == "path" => false,
+        [cmd] if cmd == "show" => true,
+        [cmd] if cmd == "check" => return check_config(),
+        _ => return Err(String::from("usage: notilog config <path|show|check>")),
+    };
+
+    let config = app_config::load_or_create();
+    if !show {
+        println!("{}", app_config::config_path().display());
+        return Ok(());
+    }
+
+    let payload = json!({
+        "config_path": app_config::config_path().display().to_string(),
+        "log_file_path": config.log_file_path.display().to_string(),
+        "max_notification_length": config.max_notification_length,
+        "max_age_days": config.max_age_days,
+        "refresh_signal": config.refresh_signal,
+        "dbus_services": config.dbus_services,
+        "logger_start_retries": config.logger_start_retries,
+        "logger_start_backoff_secs": config.logger_start_backoff_secs,
+        "timezone": config.timezone.map(|timezone| timezone.name()),
+    });
+    println!(
+        "{}",
+        serde_json::to_string(&payload)
+            .map_err(|error| format!("could not encode config: {error}"))?
+    );
+    Ok(())
+}
+
+fn handle_vacuum() -> Result<(), String> {
+    let path = log_path()?;
+    if !path.exists() {
+        println!("duplicates: 0");
+        println!("corrupt: 0");
+        println!("remaining: 0");
+        return Ok(());
+    }
+
+    let reader = open_log_reader(&path)?;
+
+    let mut seen = HashSet::new();
+    let mut records = Vec::new();
+    let mut duplicates = 0usize;
+    let mut corrupt = 0usize;
+    for line in reader.lines() {
+        let line = line.map_err(|error| format!("could not read {}: {error}", path.display()))?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let value = serde_json::from_str::<Value>(line).ok();
+        // A record logged twice differs only in its ingest `seq`.
+        let dedup_key = match &value {
+            Some(Value::Object(fields)) => {
+                let mut fields = fields.clone();
+                fields.remove("seq");
+                Value::Object(fields).to_string()
+            }
+            _ => line.to_string(),
+        };
+        if !seen.insert(dedup_key) {
+            duplicates += 1;
+            continue;
+        }
+
+        match value.and_then(|value| value_to_record(&value)) {
+            Some(record) => records.push(record),
+            None => corrupt += 1,
+        }
+    }
+
+    let records = sort_records_by_event(records);
+    write_records(&path, &records)?;
+
+    println!("duplicates: {duplicates}");
+    println!("corrupt: {corrupt}");
+    println!("remaining: {}", records.len());
+    Ok(())
+}
+
+fn run_logger() -> Result<(), String> {
+    let path = log_path()?;
+    let retention = retention();
+    let refresh_signal = refresh_signal_channel();
+
+    let mut child = start_monitor()?;
+
+    let Some(stdout) = child.stdout.take() else {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(String::from("failed to capture busctl stdout"));
+    };
+
+    // The read loop only ends when busctl exits, so on SIGINT/SIGTERM kill the
+    // child and let the loop drain to EOF; the process then exits through the
+    // normal path and never stops midway through a log line.
+    let child = Arc::new(Mutex::new(child));
+    let stopping = Arc::new(AtomicBool::new(false));
+    let handler_child = Arc::clone(&child);
+    let handler_stopping = Arc::clone(&stopping);
+    let handler = ctrlc::set_handler(move || {
+        handler_stopping.store(true, Ordering::SeqCst);
+        if let Ok(mut child) = handler_child.lock() {
+            let _ = child.kill();
+        }
+    });
+
+    let result = handler
+        .map_err(|error| format!("could not install signal handler: {error}"))
+        .and_then(|()| {
+            process_monitor_output(
+                BufReader::new(stdout),
+                &path,
+                &retention,
+                refresh_signal,
+                dedup_window_secs(),
+            )
+        });
+
+    let mut child = child
+        .lock()
+        .map_err(|_| String::from("busctl monitor handle is poisoned"))?;
+    if result.is_err() {
+        let _ = child.kill();
+    }
+    let status = child
+        .wait()
+        .map_err(|error| format!("could not wait for busctl monitor: {error}"))?;
+    result?;
+    if status.success() || stopping.load(Ordering::SeqCst) {
+        Ok(())
+    } else {
+        Err(format!("busctl monitor exited with status {status}"))
+    }
+}
+
+/// Spawns `busctl monitor`, retrying with doubling waits while it cannot start
+/// or exits right away (the session bus may not be up yet at boot).
+fn start_monitor() -> Result<Child, String> {
+    let config = app_config::load_or_create();
+    let attempts = config.logger_start_retries + 1;
+    let mut backoff = Duration::from_secs(config.logger_start_backoff_secs);
+    let mut attempt = 1;
+    loop {
+        let error = match Command::new("busctl")
+            .args(["--user", "monitor"])
+            .args(&config.dbus_services)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+        {
+            Ok(mut child) => {
+                thread::sleep(MONITOR_STARTUP_GRACE);
+                match child.try_wait() {
+                    Ok(Some(status)) if !status.success() => {
+                        format!("busctl monitor exited with status {status}")
+                    }
+                    _ => return Ok(child),
+                }
+            }
+            Err(error) => format!("could not start busctl monitor: {error}"),
+        };
+        if attempt == attempts {
+            return Err(error);
+        }
+        eprintln!(
+            "warning: attempt {attempt}/{attempts}: {error}; retrying in {}s",
+            backoff.as_secs()
+        );
+        thread::sleep(backoff);
+        backoff = (backoff * 2).min(MAX_MONITOR_BACKOFF);
+        attempt += 1;
+    }
+}
+
+fn replay_monitor_output(reader: impl BufRead) -> Result<(), String> {
     let path = log_path()?;
-    let max_notification_length = max_notification_length();
+    let retention = retention();
     let refresh_signal = refresh_signal_channel();
+    process_monitor_output(
+        reader,
+        &path,
+        &retention,
+        refresh_signal,
+        dedup_window_secs(),
+    )
+}
 
-    let mut child = Command::new("busctl")
-        .args(["--user", "monitor", "org.freedesktop.Notifications"])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::inherit())
-        .spawn()
-        .map_err(|error| format!("could not start busctl monitor: {error}"))?;
-
-    let stdout = child
-        .stdout
-        .take()
-        .ok_or_else(|| String::from("failed to capture busctl stdout"))?;
-    let reader = BufReader::new(stdout);
-
-    let mut pending: HashMap<u64, PendingNotify> = HashMap::new();
-    let mut active_events: HashMap<u32, String> = HashMap::new();
+fn process_monitor_output(
+    reader: impl BufRead,
+    path: &PathBuf,
+    retention: &Retention,
+    refresh_signal: u8,
+    dedup_window_secs: u64,
+) -> Result<(), String> {
+    let mut state = MonitorState::new(path, dedup_window_secs);
     let mut block: Vec<String> = Vec::new();
 
     for line in reader.lines() {
         let line = line.map_err(|error| format!("error reading monitor output: {error}"))?;
 
         if line.starts_with('‣') && line.contains("Type=") {
-            process_block(
-                &block,
-                &mut pending,
-                &mut active_events,
-                &path,
-                max_notification_length,
-                refresh_signal,
-            )?;
+            process_block(&block, &mut state, path, retention, refresh_signal)?;
             block.clear();
         }
 
@@ -385,31 +2091,65 @@ fn run_logger() -> Result<(), String> {
         }
     }
 
-    process_block(
-        &block,
-        &mut pending,
-        &mut active_events,
-        &path,
-        max_notification_length,
-        refresh_signal,
-    )?;
+    process_block(&block, &mut state, path, retention, refresh_signal)
+}
 
-    let status = child
-        .wait()
-        .map_err(|error| format!("could not wait for busctl monitor: {error}"))?;
-    if status.success() {
-        Ok(())
-    } else {
-        Err(format!("busctl monitor exited with status {status}"))
+/// Parser state kept across `busctl monitor` blocks.
+struct MonitorState {
+    /// `Notify` calls waiting for their reply (which carries the id), by cookie.
+    pending: HashMap<u64, PendingNotify>,
+    /// Event uid of each notification id logged this session and not closed.
+    active_events: HashMap<u32, String>,
+    /// Event uid and open epoch of notifications still open in the log when
+    /// the logger started, for closes whose `Notify` came before it.
+    startup_open: HashMap<u32, (String, Option<i64>)>,
+    dedup: Dedup,
+    /// Ids of repeats skipped by `dedup`, whose closes are skipped too.
+    suppressed: HashSet<u32>,
+    /// `seq` for the next logged record.
+    next_seq: u64,
+}
+
+impl MonitorState {
+    /// Numbers records on from the log's highest `seq` and notes the open
+    /// notifications, reading the log once here so appends and closes never
+    /// depend on reading the whole log.
+    fn new(path: &PathBuf, dedup_window_secs: u64) -> Self {
+        let records = read_records(path).unwrap_or_else(|error| {
+            eprintln!("warning: could not read log, numbering records from 0: {error}");
+            Vec::new()
+        });
+        Self {
+            pending: HashMap::new(),
+            active_events: HashMap::new(),
+            startup_open: open_events(&records),
+            dedup: Dedup::new(dedup_window_secs),
+            suppressed: HashSet::new(),
+            next_seq: next_seq(&records),
+        }
+    }
+
+    /// Uid of the notification `id` left open before the logger started, if it
+    /// opened within `ORPHAN_CLOSE_WINDOW_SECS` of `closed_epoch`.
+    fn take_startup_open(&mut self, id: u32, closed_epoch: Option<i64>) -> Option<String> {
+        self.startup_open
+            .remove(&id)
+            .filter(|(_, opened)| within_orphan_window(*opened, closed_epoch))
+            .map(|(event_uid, _)| event_uid)
+    }
+
+    fn take_seq(&mut self) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        seq
     }
 }
 
 fn process_block(
     block: &[String],
-    pending: &mut HashMap<u64, PendingNotify>,
-    active_events: &mut HashMap<u32, String>,
+    state: &mut MonitorState,
     path: &PathBuf,
-    max_notification_length: usize,
+    retention: &Retention,
     refresh_signal: u8,
 ) -> Result<(), String> {
     if block.is_empty() {
@@ -418,24 +2158,48 @@ fn process_block(
 
     let header = &block[0];
     let msg_type = token_value(header, "Type=");
+    let member = block
+        .iter()
+        .find_map(|line| token_value(line, "Member="))
+        .unwrap_or_default();
+    log_diagnostic(LogLevel::Debug, || {
+        format!(
+            "block type={} member={member}",
+            msg_type.as_deref().unwrap_or("?")
+        )
+    });
 
     if msg_type.as_deref() == Some("method_call") && block_contains(block, "Member=Notify") {
         let cookie = token_value(header, "Cookie=").and_then(|value| value.parse::<u64>().ok());
         let timestamp = quoted_value_after(header, "Timestamp=");
         let strings = extract_strings(block);
 
-        if let (Some(cookie), Some(timestamp)) = (cookie, timestamp) {
-            if strings.len() >= 4 {
-                let notify = PendingNotify {
-                    timestamp,
-                    app_name: strings[0].clone(),
-                    summary: strings[2].clone(),
-                    body: strings[3].clone(),
-                };
-                pending.insert(cookie, notify);
-            }
+        if strings.len() < 4 {
+            log_diagnostic(LogLevel::Warn, || {
+                format!(
+                    "Notify has {} string(s), expected at least 4; skipped",
+                    strings.len()
+                )
+            });
+            return Ok(());
         }
+        let (Some(cookie), Some(timestamp)) = (cookie, timestamp) else {
+            log_diagnostic(LogLevel::Warn, || {
+                String::from("Notify header has no Cookie or Timestamp; skipped")
+            });
+            return Ok(());
+        };
 
+        log_diagnostic(LogLevel::Debug, || {
+            format!("queued Notify cookie={cookie} summary={:?}", strings[2])
+        });
+        let notify = PendingNotify {
+            timestamp,
+            app_name: strings[0].clone(),
+            summary: strings[2].clone(),
+            body: strings[3].clone(),
+        };
+        state.pending.insert(cookie, notify);
         return Ok(());
     }
 
@@ -446,17 +2210,36 @@ fn process_block(
             return Ok(());
         };
 
-        let Some(notify) = pending.remove(&reply_cookie) else {
+        let Some(notify) = state.pending.remove(&reply_cookie) else {
+            log_diagnostic(LogLevel::Debug, || {
+                format!("reply to cookie={reply_cookie} is not a pending Notify; no record")
+            });
             return Ok(());
         };
 
         let Some(id) = first_uint32(block) else {
+            log_diagnostic(LogLevel::Warn, || {
+                format!("Notify reply for cookie={reply_cookie} has no id; dropped")
+            });
             return Ok(());
         };
 
         let (epoch, hhmm) = timestamp_to_epoch_and_hhmm(&notify.timestamp).unwrap_or((None, None));
+        if let Some(original) = state.dedup.repeat_of(&notify, epoch) {
+            log_diagnostic(LogLevel::Debug, || {
+                format!("Notify id={id} repeats {original}; skipped")
+            });
+            // The original keeps its own close; this one's is not logged.
+            state.active_events.remove(&id);
+            state.suppressed.insert(id);
+            return Ok(());
+        }
         let event_uid = make_event_uid(id, &notify.timestamp);
-        active_events.insert(id, event_uid.clone());
+        state.suppressed.remove(&id);
+        state.active_events.insert(id, event_uid.clone());
+        if let Some(epoch) = epoch {
+            state.dedup.remember(&notify, epoch, &event_uid);
+        }
         let (body_source, body_text) = split_body_fields(&notify.body);
 
         let payload = json!({
@@ -469,9 +2252,13 @@ fn process_block(
             "summary": notify.summary,
             "body_source": body_source,
             "body": body_text,
+            "seq": state.take_seq(),
         });
 
-        append_payload(path, &payload, max_notification_length)?;
+        append_payload(path, &payload, retention)?;
+        log_diagnostic(LogLevel::Debug, || {
+            format!("recorded notification id={id} event_uid={event_uid}")
+        });
         if let Err(error) = trigger_refresh_signal(refresh_signal) {
             eprintln!("warning: failed to trigger refresh signal: {error}");
         }
@@ -485,15 +2272,33 @@ fn process_block(
 
         let values = uint32_values(block);
         if values.len() < 2 {
+            log_diagnostic(LogLevel::Warn, || {
+                String::from("NotificationClosed has fewer than 2 integers; skipped")
+            });
             return Ok(());
         }
 
         let id = values[0];
+        if state.suppressed.remove(&id) {
+            log_diagnostic(LogLevel::Debug, || {
+                format!("close for skipped repeat id={id}; not recorded")
+            });
+            return Ok(());
+        }
         let reason_code = values[1];
         let reason = close_reason_label(reason_code);
         let (closed_epoch, closed_hhmm) =
             timestamp_to_epoch_and_hhmm(&timestamp).unwrap_or((None, None));
-        let event_uid = active_events.remove(&id);
+        let event_uid = state.active_events.remove(&id).or_else(|| {
+            let found = state.take_startup_open(id, closed_epoch);
+            log_diagnostic(LogLevel::Warn, || match &found {
+                Some(event_uid) => {
+                    format!("close for id={id} not seen this session; attached to {event_uid}")
+                }
+                None => format!("close for id={id} matches no active notification"),
+            });
+            found
+        });
 
         let payload = json!({
             "event_uid": event_uid,
@@ -503,22 +2308,73 @@ fn process_block(
             "closed_epoch": closed_epoch,
             "closed_hhmm": closed_hhmm,
             "closed_bus_timestamp": timestamp,
+            "seq": state.take_seq(),
         });
 
-        append_payload(path, &payload, max_notification_length)?;
+        append_payload(path, &payload, retention)?;
+        log_diagnostic(LogLevel::Debug, || {
+            format!("recorded close id={id} reason={reason}")
+        });
         if let Err(error) = trigger_refresh_signal(refresh_signal) {
             eprintln!("warning: failed to trigger refresh signal: {error}");
         }
+        return Ok(());
     }
 
+    log_diagnostic(LogLevel::Debug, || String::from("block ignored"));
     Ok(())
 }
 
-fn append_payload(
-    path: &PathBuf,
-    payload: &Value,
-    max_notification_length: usize,
-) -> Result<(), String> {
+/// Notifications logged within the last `window_secs`, keyed by
+/// (app_name, summary, body), for `dedup_window_secs`.
+#[derive(Debug)]
+struct Dedup {
+    window_secs: i64,
+    logged: HashMap<(String, String, String), (i64, String)>,
+}
+
+impl Dedup {
+    fn new(window_secs: u64) -> Self {
+        Self {
+            window_secs: i64::try_from(window_secs).unwrap_or(i64::MAX),
+            logged: HashMap::new(),
+        }
+    }
+
+    fn key(notify: &PendingNotify) -> (String, String, String) {
+        (
+            notify.app_name.clone(),
+            notify.summary.clone(),
+            notify.body.clone(),
+        )
+    }
+
+    /// Event uid of an identical notification logged inside the window.
+    /// The window is measured from that logged one, so steady spam is still
+    /// recorded once per window.
+    fn repeat_of(&self, notify: &PendingNotify, epoch: Option<i64>) -> Option<String> {
+        if self.window_secs == 0 {
+            return None;
+        }
+        let epoch = epoch?;
+        let (logged_epoch, event_uid) = self.logged.get(&Self::key(notify))?;
+        (epoch.saturating_sub(*logged_epoch) < self.window_secs).then(|| event_uid.clone())
+    }
+
+    fn remember(&mut self, notify: &PendingNotify, epoch: i64, event_uid: &str) {
+        if self.window_secs == 0 {
+            return;
+        }
+        let window_secs = self.window_secs;
+        self.logged
+            .retain(|_, (logged_epoch, _)| epoch.saturating_sub(*logged_epoch) < window_secs);
+        self.logged
+            .insert(Self::key(notify), (epoch, event_uid.to_string()));
+    }
+}
+
+fn append_payload(path: &PathBuf, payload: &Value, retention: &Retention) -> Result<(), String> {
+    ensure_plain_log(path)?;
     let mut log_file = OpenOptions::new()
         .create(true)
         .append(true)
@@ -532,14 +2388,59 @@ fn append_payload(
         .flush()
         .map_err(|error| format!("could not flush log file: {error}"))?;
 
-    prune_to_max_notifications(path, max_notification_length)
+    prune_to_max_notifications(path, retention)
 }
 
-fn prune_to_max_notifications(
-    path: &PathBuf,
-    max_notification_length: usize,
-) -> Result<(), String> {
-    if max_notification_length == 0 {
+fn append_records(path: &PathBuf, records: &[LogRecord]) -> Result<(), String> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    ensure_plain_log(path)?;
+
+    let mut log_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|error| format!("could not open {}: {error}", path.display()))?;
+
+    for record in records {
+        let payload = record_to_json(record);
+        serde_json::to_writer(&mut log_file, &payload)
+            .map_err(|error| format!("could not write log JSON: {error}"))?;
+        writeln!(log_file).map_err(|error| format!("could not write log newline: {error}"))?;
+    }
+
+    log_file
+        .flush()
+        .map_err(|error| format!("could not flush log file: {error}"))
+}
+
+/// One past the highest `seq` in `records`. Only ingested records (the
+/// logger's and `import`'s) are numbered; updates appended by `mark-user`,
+/// `notitui`, ... rank by their notification's other records.
+fn next_seq(records: &[LogRecord]) -> u64 {
+    records
+        .iter()
+        .filter_map(|record| record.seq)
+        .max()
+        .map_or(0, |seq| seq + 1)
+}
+
+/// Log retention limits from the config; a notification is kept only while it
+/// satisfies every limit that is set.
+#[derive(Debug, Clone)]
+struct Retention {
+    max_notifications: usize,
+    max_age_days: Option<u64>,
+    /// Per-app caps keyed by lowercased `app_name`, applied before the global cap.
+    per_app: HashMap<String, usize>,
+}
+
+fn prune_to_max_notifications(path: &PathBuf, retention: &Retention) -> Result<(), String> {
+    if retention.max_notifications == 0
+        && retention.max_age_days.is_none()
+        && retention.per_app.is_empty()
+    {
         return Ok(());
     }
 
@@ -549,7 +2450,17 @@ fn prune_to_max_notifications(
     }
 
     let before = records.len();
-    let trimmed = trim_records_to_latest_notifications(records, max_notification_length);
+    let mut trimmed = records;
+    if !retention.per_app.is_empty() {
+        trimmed = trim_records_per_app(trimmed, &retention.per_app);
+    }
+    if retention.max_notifications > 0 {
+        trimmed = trim_records_to_latest_notifications(trimmed, retention.max_notifications);
+    }
+    if let Some(days) = retention.max_age_days {
+        let cutoff = now_epoch().saturating_sub((days as i64).saturating_mul(24 * 60 * 60));
+        trimmed = drop_notifications_older_than(trimmed, cutoff);
+    }
     if trimmed.len() == before {
         return Ok(());
     }
@@ -561,9 +2472,10 @@ fn trim_records_to_latest_notifications(
     records: Vec<LogRecord>,
     max_notification_length: usize,
 ) -> Vec<LogRecord> {
+    let keys = record_event_keys(&records);
     let mut order: HashMap<String, (i64, usize)> = HashMap::new();
     for (index, record) in records.iter().enumerate() {
-        let key = record_event_key(record, index);
+        let key = keys[index].clone();
         let epoch = event_epoch(record).unwrap_or(0);
         order
             .entry(key)
@@ -598,78 +2510,419 @@ fn trim_records_to_latest_notifications(
         .into_iter()
         .enumerate()
         .filter_map(|(index, record)| {
-            let key = record_event_key(&record, index);
-            if keep.contains(&key) {
+            if keep.contains(&keys[index]) {
                 Some(record)
             } else {
                 None
             }
         })
-        .collect()
+        .collect()
+}
+
+/// Keeps only the newest notifications of each app listed in `limits`; other
+/// apps and notifications without an app name are left alone.
+fn trim_records_per_app(
+    records: Vec<LogRecord>,
+    limits: &HashMap<String, usize>,
+) -> Vec<LogRecord> {
+    let keys = record_event_keys(&records);
+    let mut order: HashMap<&str, (i64, usize)> = HashMap::new();
+    let mut apps: HashMap<&str, String> = HashMap::new();
+    for (index, record) in records.iter().enumerate() {
+        let key = keys[index].as_str();
+        let epoch = event_epoch(record).unwrap_or(0);
+        order
+            .entry(key)
+            .and_modify(|best| {
+                if epoch > best.0 || (epoch == best.0 && index > best.1) {
+                    *best = (epoch, index);
+                }
+            })
+            .or_insert((epoch, index));
+        if let Some(app_name) = record.app_name.as_deref() {
+            apps.insert(key, app_name.to_lowercase());
+        }
+    }
+
+    let mut by_app: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (key, app_name) in &apps {
+        if limits.contains_key(app_name) {
+            by_app.entry(app_name.as_str()).or_default().push(key);
+        }
+    }
+
+    let mut dropped = HashSet::new();
+    for (app_name, mut ranked) in by_app {
+        ranked.sort_by_key(|key| std::cmp::Reverse(order[key]));
+        dropped.extend(ranked.into_iter().skip(limits[app_name]));
+    }
+    if dropped.is_empty() {
+        return records;
+    }
+
+    records
+        .into_iter()
+        .zip(&keys)
+        .filter_map(|(record, key)| (!dropped.contains(key.as_str())).then_some(record))
+        .collect()
+}
+
+/// Drops every record of notifications whose newest timestamp is before
+/// `cutoff`; notifications without any timestamp are kept.
+fn drop_notifications_older_than(records: Vec<LogRecord>, cutoff: i64) -> Vec<LogRecord> {
+    let keys = record_event_keys(&records);
+    let mut newest: HashMap<&str, Option<i64>> = HashMap::new();
+    for (index, record) in records.iter().enumerate() {
+        let entry = newest.entry(keys[index].as_str()).or_insert(None);
+        *entry = (*entry).max(event_epoch(record));
+    }
+
+    let keep = keys
+        .iter()
+        .map(|key| newest[key.as_str()].is_none_or(|epoch| epoch >= cutoff))
+        .collect::<Vec<_>>();
+    records
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(record, keep)| keep.then_some(record))
+        .collect()
+}
+
+/// Ordering key of the record on log line `index`; a notification ranks by
+/// its highest record. `seq` keeps records without timestamps, or with equal
+/// ones, in ingest order even after the log is rewritten.
+fn record_rank(record: &LogRecord, index: usize) -> (i64, u64, usize) {
+    (
+        event_epoch(record).unwrap_or(0),
+        record.seq.unwrap_or(0),
+        index,
+    )
+}
+
+/// Orders raw records oldest notification first (using the same ordering key as
+/// `aggregate_records`) while keeping each notification's records in file order.
+fn sort_records_by_event(records: Vec<LogRecord>) -> Vec<LogRecord> {
+    let keys = record_event_keys(&records);
+    let mut order: HashMap<String, (i64, u64, usize)> = HashMap::new();
+    for (index, record) in records.iter().enumerate() {
+        let key = keys[index].clone();
+        let rank = record_rank(record, index);
+        order
+            .entry(key)
+            .and_modify(|best| *best = (*best).max(rank))
+            .or_insert(rank);
+    }
+
+    let mut keyed = records
+        .into_iter()
+        .enumerate()
+        .map(|(index, record)| {
+            let rank = order.get(&keys[index]).copied().unwrap_or((0, 0, index));
+            (rank, index, record)
+        })
+        .collect::<Vec<_>>();
+    keyed.sort_by(|left, right| left.0.cmp(&right.0).then_with(|| left.1.cmp(&right.1)));
+    keyed.into_iter().map(|(_, _, record)| record).collect()
+}
+
+fn aggregate_records(records: &[LogRecord]) -> Vec<LogRecord> {
+    let keys = record_event_keys(records);
+    let mut merged: HashMap<String, LogRecord> = HashMap::new();
+    let mut order: HashMap<String, (i64, u64, usize)> = HashMap::new();
+
+    for (idx, record) in records.iter().enumerate() {
+        let key = keys[idx].clone();
+        let entry = merged
+            .entry(key.clone())
+            .or_insert_with(|| LogRecord::empty(record.id));
+        if entry.event_uid.is_none() {
+            entry.event_uid = Some(key.clone());
+        }
+        entry.merge_from(record);
+
+        let rank = record_rank(record, idx);
+        order
+            .entry(key)
+            .and_modify(|best| *best = (*best).max(rank))
+            .or_insert(rank);
+    }
+
+    let mut values: Vec<LogRecord> = merged.into_values().collect();
+    values.sort_by(|left, right| {
+        let left_key = left.event_uid.clone().unwrap_or_default();
+        let right_key = right.event_uid.clone().unwrap_or_default();
+        let left_order = order.get(&left_key).copied().unwrap_or_default();
+        let right_order = order.get(&right_key).copied().unwrap_or_default();
+        right_order.cmp(&left_order)
+    });
+    values
+}
+
+fn record_to_json(record: &LogRecord) -> Value {
+    json!({
+        "event_uid": record.event_uid,
+        "id": record.id,
+        "epoch": record.epoch,
+        "hhmm": record.hhmm,
+        "app_name": record.app_name,
+        "summary": record.summary,
+        "body_source": record.body_source,
+        "body": record.body,
+        "close_reason_code": record.close_reason_code,
+        "close_reason": record.close_reason,
+        "closed_epoch": record.closed_epoch,
+        "closed_hhmm": record.closed_hhmm,
+        "seq": record.seq,
+    })
+}
+
+/// JSON Schema for one object produced by `record_to_json`.
+fn record_json_schema() -> Value {
+    let nullable = |kind: &str| json!({ "type": [kind, "null"] });
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "notilog record",
+        "type": "object",
+        "required": ["id"],
+        "properties": {
+            "event_uid": nullable("string"),
+            "id": { "type": "integer", "minimum": 0, "maximum": u32::MAX },
+            "epoch": nullable("integer"),
+            "hhmm": nullable("string"),
+            "app_name": nullable("string"),
+            "summary": nullable("string"),
+            "body_source": nullable("string"),
+            "body": nullable("string"),
+            "close_reason_code": {
+                "type": ["integer", "null"],
+                "minimum": 0,
+                "maximum": u32::MAX,
+            },
+            "close_reason": nullable("string"),
+            "closed_epoch": nullable("integer"),
+            "closed_hhmm": nullable("string"),
+            "seq": { "type": ["integer", "null"], "minimum": 0 },
+        },
+    })
+}
+
+fn check_config() -> Result<(), String> {
+    let (_, issues) = app_config::load_with_issues();
+    let path = app_config::config_path();
+    if issues.is_empty() {
+        println!("{}: ok", path.display());
+        return Ok(());
+    }
+
+    for issue in &issues {
+        eprintln!("{}: {issue}", path.display());
+    }
+    Err(format!("{} problem(s) found in config", issues.len()))
+}
+
+/// Outcome of one `doctor` check: what was found, or the problem and a hint.
+type DoctorCheck = Result<String, (String, String)>;
+
+fn handle_doctor(args: Vec<String>) -> Result<(), String> {
+    if !args.is_empty() {
+        return Err(String::from("usage: notilog doctor"));
+    }
+    // Checked before loading: loading writes a default config when missing.
+    let config_existed = app_config::config_path().exists();
+    let (config, issues) = app_config::load_with_issues();
+    let path = log_path()?;
+
+    let checks: [(&str, DoctorCheck); 5] = [
+        ("config", doctor_config(config_existed, &issues)),
+        ("log file", doctor_log_file(&path)),
+        ("busctl", doctor_busctl()),
+        ("session bus", doctor_session_bus(&config.dbus_services)),
+        ("recent records", doctor_recent_records(&path)),
+    ];
+    let mut failed = 0;
+    for (name, check) in &checks {
+        match check {
+            Ok(detail) => println!("[ok]   {name}: {detail}"),
+            Err((problem, hint)) => {
+                failed += 1;
+                println!("[fail] {name}: {problem}");
+                println!("       hint: {hint}");
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{failed} check(s) failed"));
+    }
+    Ok(())
+}
+
+fn doctor_config(existed: bool, issues: &[String]) -> DoctorCheck {
+    let path = app_config::config_path();
+    if !existed {
+        return Err((
+            format!("{} was missing; a default one was written", path.display()),
+            String::from("review it and run `notilog doctor` again"),
+        ));
+    }
+    if !issues.is_empty() {
+        return Err((
+            format!("{} problem(s) in {}", issues.len(), path.display()),
+            String::from("run `notilog config check` to list them"),
+        ));
+    }
+    Ok(format!("{} parses cleanly", path.display()))
+}
+
+fn doctor_log_file(path: &PathBuf) -> DoctorCheck {
+    if !path.exists() {
+        return Err((
+            format!("{} does not exist", path.display()),
+            String::from("start the logger with `notilog logger run`; it creates the file"),
+        ));
+    }
+    if let Err(error) = ensure_plain_log(path) {
+        return Err((
+            error,
+            String::from("point log_file_path (or --log-file) at the live, uncompressed log"),
+        ));
+    }
+    match OpenOptions::new().append(true).open(path) {
+        Ok(_) => Ok(format!("{} is writable", path.display())),
+        Err(error) => Err((
+            format!("{} is not writable: {error}", path.display()),
+            String::from("check the file's owner and permissions"),
+        )),
+    }
+}
+
+fn doctor_busctl() -> DoctorCheck {
+    match Command::new("busctl").arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout);
+            Ok(version.lines().next().unwrap_or("found").trim().to_string())
+        }
+        Ok(output) => Err((
+            format!("busctl --version exited with status {}", output.status),
+            String::from("reinstall systemd's busctl"),
+        )),
+        Err(error) => Err((
+            format!("could not run busctl: {error}"),
+            String::from("install systemd (busctl) and make sure it is on PATH"),
+        )),
+    }
+}
+
+fn doctor_session_bus(services: &[String]) -> DoctorCheck {
+    let hint = || {
+        String::from(
+            "run inside your graphical session, or check DBUS_SESSION_BUS_ADDRESS / `systemctl --user status dbus`",
+        )
+    };
+    let output = Command::new("busctl")
+        .args(["--user", "--no-pager", "list"])
+        .output()
+        .map_err(|error| (format!("could not run busctl: {error}"), hint()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err((format!("not reachable: {}", stderr.trim()), hint()));
+    }
+    let names = String::from_utf8_lossy(&output.stdout);
+    let missing = services
+        .iter()
+        .filter(|service| {
+            !names
+                .lines()
+                .any(|line| line.split_whitespace().next() == Some(service.as_str()))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        Ok(format!("reachable; {} present", services.join(", ")))
+    } else {
+        Err((
+            format!("reachable, but no owner for {}", missing.join(", ")),
+            String::from("start a notification daemon (mako, dunst, swaync, ...)"),
+        ))
+    }
 }
 
-fn aggregate_records(records: &[LogRecord]) -> Vec<LogRecord> {
-    let mut merged: HashMap<String, LogRecord> = HashMap::new();
-    let mut order: HashMap<String, (i64, usize)> = HashMap::new();
-
-    for (idx, record) in records.iter().enumerate() {
-        let key = record
-            .event_uid
-            .clone()
-            .unwrap_or_else(|| format!("legacy:{}:{idx}", record.id));
-        let entry = merged
-            .entry(key.clone())
-            .or_insert_with(|| LogRecord::empty(record.id));
-        if entry.event_uid.is_none() {
-            entry.event_uid = Some(key.clone());
-        }
-        entry.merge_from(record);
+fn doctor_recent_records(path: &PathBuf) -> DoctorCheck {
+    let hint = || {
+        String::from(
+            "check the logger with `notilog logger status` or `systemctl --user status notilog`",
+        )
+    };
+    let records = read_records(path).map_err(|error| (error, hint()))?;
+    let Some(newest) = records.iter().filter_map(event_epoch).max() else {
+        return Err((String::from("the log has no records yet"), hint()));
+    };
+    let age = now_epoch().saturating_sub(newest);
+    if age > DOCTOR_RECENT_SECS {
+        return Err((
+            format!("newest record is {} day(s) old", age / (24 * 60 * 60)),
+            hint(),
+        ));
+    }
+    Ok(format!(
+        "{} records, newest {}s ago",
+        records.len(),
+        age.max(0)
+    ))
+}
 
-        let epoch = event_epoch(record).unwrap_or(0);
-        match order.get_mut(&key) {
-            Some((best_epoch, best_idx)) => {
-                if epoch > *best_epoch || (epoch == *best_epoch && idx > *best_idx) {
-                    *best_epoch = epoch;
-                    *best_idx = idx;
-                }
-            }
-            None => {
-                order.insert(key, (epoch, idx));
-            }
-        }
+fn handle_json_schema(args: Vec<String>) -> Result<(), String> {
+    if !args.is_empty() {
+        return Err(String::from("usage: notilog json-schema"));
     }
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&record_json_schema())
+            .map_err(|error| format!("could not encode schema: {error}"))?
+    );
+    Ok(())
+}
 
-    let mut values: Vec<LogRecord> = merged.into_values().collect();
-    values.sort_by(|left, right| {
-        let left_key = left.event_uid.clone().unwrap_or_default();
-        let right_key = right.event_uid.clone().unwrap_or_default();
-        let left_order = order.get(&left_key).copied().unwrap_or((0, 0));
-        let right_order = order.get(&right_key).copied().unwrap_or((0, 0));
-        right_order
-            .0
-            .cmp(&left_order.0)
-            .then_with(|| right_order.1.cmp(&left_order.1))
-    });
-    values
+fn encode_json(value: &Value, pretty: bool) -> serde_json::Result<String> {
+    if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
 }
 
-fn record_to_json(record: &LogRecord) -> Value {
-    json!({
-        "event_uid": record.event_uid,
-        "id": record.id,
-        "epoch": record.epoch,
-        "hhmm": record.hhmm,
-        "app_name": record.app_name,
-        "summary": record.summary,
-        "body_source": record.body_source,
-        "body": record.body,
-        "close_reason_code": record.close_reason_code,
-        "close_reason": record.close_reason,
-        "closed_epoch": record.closed_epoch,
-        "closed_hhmm": record.closed_hhmm,
+/// Encodes `fields` of `value` as one object with the keys in that order
+/// (serde_json's own maps sort their keys), laid out like `encode_json`.
+fn encode_fields(value: &Value, fields: &[String], pretty: bool) -> serde_json::Result<String> {
+    let mut entries = Vec::with_capacity(fields.len());
+    for field in fields {
+        let key = serde_json::to_string(field)?;
+        let encoded = encode_json(&value[field.as_str()], pretty)?;
+        entries.push(if pretty {
+            format!("  {key}: {}", encoded.replace('\n', "\n  "))
+        } else {
+            format!("{key}:{encoded}")
+        });
+    }
+    Ok(if pretty && !entries.is_empty() {
+        format!("{{\n{}\n}}", entries.join(",\n"))
+    } else {
+        format!("{{{}}}", entries.join(","))
     })
 }
 
+/// Wraps already encoded values in a JSON array, laid out like `encode_json`.
+fn join_json_array(encoded: &[String], pretty: bool) -> String {
+    if pretty && !encoded.is_empty() {
+        let items = encoded
+            .iter()
+            .map(|item| format!("  {}", item.replace('\n', "\n  ")))
+            .collect::<Vec<_>>();
+        format!("[\n{}\n]", items.join(",\n"))
+    } else {
+        format!("[{}]", encoded.join(","))
+    }
+}
+
 fn event_epoch(record: &LogRecord) -> Option<i64> {
     record.closed_epoch.or(record.epoch)
 }
@@ -716,17 +2969,20 @@ fn quoted_value_after(line: &str, key: &str) -> Option<String> {
 fn extract_strings(block: &[String]) -> Vec<String> {
     let mut strings = Vec::new();
     let mut multiline: Option<String> = None;
+    // Whether an unescaped quote inside the current value is still open.
+    let mut inner_quote = false;
 
-    for line in block {
+    for (index, line) in block.iter().enumerate() {
         let trimmed = line.trim_start();
+        let next_line = block.get(index + 1).map(String::as_str);
 
         if let Some(mut current) = multiline.take() {
             if !current.is_empty() {
                 current.push('\n');
             }
-            if let Some(end) = find_closing_quote(trimmed) {
+            if let Some(end) = find_closing_quote(trimmed, &mut inner_quote, next_line) {
                 current.push_str(&trimmed[..end]);
-                strings.push(current);
+                strings.push(unescape_busctl_string(&current));
             } else {
                 current.push_str(trimmed);
                 multiline = Some(current);
@@ -742,8 +2998,9 @@ fn extract_strings(block: &[String]) -> Vec<String> {
             continue;
         };
         let rest = &trimmed[start + 1..];
-        if let Some(end) = find_closing_quote(rest) {
-            strings.push(rest[..end].to_string());
+        inner_quote = false;
+        if let Some(end) = find_closing_quote(rest, &mut inner_quote, next_line) {
+            strings.push(unescape_busctl_string(&rest[..end]));
         } else {
             multiline = Some(rest.to_string());
         }
@@ -752,7 +3009,17 @@ fn extract_strings(block: &[String]) -> Vec<String> {
     strings
 }
 
-fn find_closing_quote(text: &str) -> Option<usize> {
+/// Finds the quote that terminates a busctl `STRING` value on this line of
+/// it. Only an unescaped quote followed by the trailing `;` can close it, and
+/// only when the bare quotes before it in the value are balanced
+/// (`inner_quote`, carried across lines) or the next line starts another
+/// busctl item. So a body line such as `He said "hi";` doesn't cut the
+/// value short.
+fn find_closing_quote(
+    text: &str,
+    inner_quote: &mut bool,
+    next_line: Option<&str>,
+) -> Option<usize> {
     let mut escaped = false;
     for (index, ch) in text.char_indices() {
         if escaped {
@@ -762,13 +3029,73 @@ fn find_closing_quote(text: &str) -> Option<usize> {
 
         match ch {
             '\\' => escaped = true,
-            '"' => return Some(index),
+            '"' if text[index + 1..].trim_end() == ";"
+                && (!*inner_quote || starts_busctl_item(next_line)) =>
+            {
+                return Some(index);
+            }
+            '"' => *inner_quote = !*inner_quote,
             _ => {}
         }
     }
     None
 }
 
+/// Whether a monitor line begins the next typed value or closes a container
+/// (`None` is the end of the message).
+fn starts_busctl_item(line: Option<&str>) -> bool {
+    const TYPES: &[&str] = &[
+        "BYTE",
+        "BOOLEAN",
+        "INT16",
+        "UINT16",
+        "INT32",
+        "UINT32",
+        "INT64",
+        "UINT64",
+        "DOUBLE",
+        "STRING",
+        "OBJECT_PATH",
+        "SIGNATURE",
+        "UNIX_FD",
+        "ARRAY",
+        "VARIANT",
+        "STRUCT",
+        "DICT_ENTRY",
+    ];
+    let Some(line) = line.map(str::trim_start) else {
+        return true;
+    };
+    line.starts_with('}')
+        || line
+            .split_once(' ')
+            .is_some_and(|(word, _)| TYPES.contains(&word))
+}
+
+fn unescape_busctl_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('"') => out.push('"'),
+            Some('\\') => out.push('\\'),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
 fn first_uint32(block: &[String]) -> Option<u32> {
     uint32_values(block).into_iter().next()
 }
@@ -811,8 +3138,10 @@ fn timestamp_to_epoch_and_hhmm(timestamp: &str) -> Option<(Option<i64>, Option<S
 }
 
 fn log_path() -> Result<PathBuf, String> {
-    let config = app_config::load_or_create();
-    let path = config.log_file_path;
+    let path = match LOG_FILE_OVERRIDE.get() {
+        Some(path) => path.clone(),
+        None => app_config::load_or_create().log_file_path,
+    };
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
             .map_err(|error| format!("could not create {}: {error}", parent.display()))?;
@@ -820,8 +3149,17 @@ fn log_path() -> Result<PathBuf, String> {
     Ok(path)
 }
 
-fn max_notification_length() -> usize {
-    app_config::load_or_create().max_notification_length
+fn retention() -> Retention {
+    let config = app_config::load_or_create();
+    Retention {
+        max_notifications: config.max_notification_length,
+        max_age_days: config.max_age_days,
+        per_app: config.app_retention,
+    }
+}
+
+fn dedup_window_secs() -> u64 {
+    app_config::load_or_create().dedup_window_secs
 }
 
 fn refresh_signal_channel() -> u8 {
@@ -844,21 +3182,106 @@ fn trigger_refresh_signal(signal_channel: u8) -> Result<(), String> {
     }
 }
 
-fn record_event_key(record: &LogRecord, index: usize) -> String {
-    record
-        .event_uid
-        .clone()
-        .unwrap_or_else(|| format!("legacy:{}:{index}", record.id))
+/// Assigns each raw record the key of the notification it belongs to. Close
+/// records without an `event_uid` (the logger never saw the matching
+/// `Notify`) attach to the latest still-open notification with the same id
+/// inside `ORPHAN_CLOSE_WINDOW_SECS`; anything else gets a `legacy:` key.
+fn record_event_keys(records: &[LogRecord]) -> Vec<String> {
+    let mut open_by_id: HashMap<u32, (String, Option<i64>)> = HashMap::new();
+    let mut keys = Vec::with_capacity(records.len());
+
+    for (index, record) in records.iter().enumerate() {
+        let is_close = record.close_reason_code.is_some();
+        let key = match &record.event_uid {
+            Some(event_uid) => event_uid.clone(),
+            None if is_close => open_by_id
+                .get(&record.id)
+                .filter(|(_, opened)| within_orphan_window(*opened, record.closed_epoch))
+                .map(|(key, _)| key.clone())
+                .unwrap_or_else(|| format!("legacy:{}:{index}", record.id)),
+            None => format!("legacy:{}:{index}", record.id),
+        };
+
+        if is_close {
+            if open_by_id
+                .get(&record.id)
+                .is_some_and(|(open_key, _)| *open_key == key)
+            {
+                open_by_id.remove(&record.id);
+            }
+        } else if record.epoch.is_some() {
+            open_by_id.insert(record.id, (key.clone(), record.epoch));
+        }
+        keys.push(key);
+    }
+
+    keys
+}
+
+fn within_orphan_window(opened: Option<i64>, closed: Option<i64>) -> bool {
+    match (opened, closed) {
+        (Some(opened), Some(closed)) => (0..=ORPHAN_CLOSE_WINDOW_SECS).contains(&(closed - opened)),
+        _ => true,
+    }
+}
+
+/// Uid and open epoch of the latest open notification for each id in
+/// `records`, for close signals whose `Notify` happened before this logger
+/// started.
+fn open_events(records: &[LogRecord]) -> HashMap<u32, (String, Option<i64>)> {
+    let mut open = HashMap::new();
+    for record in aggregate_records(records) {
+        if record.close_reason_code.is_some() || open.contains_key(&record.id) {
+            continue;
+        }
+        if let Some(event_uid) = record.event_uid
+            && !event_uid.starts_with("legacy:")
+        {
+            open.insert(record.id, (event_uid, record.epoch));
+        }
+    }
+    open
+}
+
+/// Log records read once per command; the merged per-notification view is
+/// built on first use and shared by later lookups.
+struct LogStore {
+    records: Vec<LogRecord>,
+    merged: OnceCell<Vec<LogRecord>>,
+}
+
+impl LogStore {
+    fn open(path: &PathBuf) -> Result<Self, String> {
+        Ok(Self {
+            records: read_records(path)?,
+            merged: OnceCell::new(),
+        })
+    }
+
+    /// Raw records in file order.
+    fn all(&self) -> &[LogRecord] {
+        &self.records
+    }
+
+    /// One record per notification, newest first (see `aggregate_records`).
+    fn merged(&self) -> &[LogRecord] {
+        self.merged.get_or_init(|| aggregate_records(&self.records))
+    }
+
+    fn merged_where(&self, predicate: impl Fn(&LogRecord) -> bool) -> Vec<&LogRecord> {
+        self.merged()
+            .iter()
+            .filter(|record| predicate(record))
+            .collect()
+    }
 }
 
 fn read_records(path: &PathBuf) -> Result<Vec<LogRecord>, String> {
-    if !path.exists() {
+    if !log_exists(path) {
         return Ok(Vec::new());
     }
 
-    let file =
-        File::open(path).map_err(|error| format!("could not open {}: {error}", path.display()))?;
-    let reader = BufReader::new(file);
+    let reader = open_log_reader(path)?;
 
     let mut records = Vec::new();
     for line in reader.lines() {
@@ -878,25 +3301,169 @@ fn read_records(path: &PathBuf) -> Result<Vec<LogRecord>, String> {
     Ok(records)
 }
 
-fn write_records(path: &PathBuf, records: &[LogRecord]) -> Result<(), String> {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .truncate(true)
-        .write(true)
-        .open(path)
-        .map_err(|error| format!("could not open {} for write: {error}", path.display()))?;
+/// Record filters for `tail`; an empty filter keeps every line.
+#[derive(Debug, Default)]
+struct TailFilter {
+    app: Option<String>,
+    reason_code: Option<u32>,
+}
 
-    for record in records {
-        let payload = record_to_json(record);
+impl TailFilter {
+    fn is_empty(&self) -> bool {
+        self.app.is_none() && self.reason_code.is_none()
+    }
+
+    /// Records without a close reason never match a `--reason` filter.
+    fn matches(&self, record: &LogRecord) -> bool {
+        let app_matches = self
+            .app
+            .as_deref()
+            .is_none_or(|app| record_matches_app(record, app));
+        let reason_matches = self.reason_code.is_none_or(|code| {
+            record.close_reason_code == Some(code)
+                || (record.close_reason_code.is_none()
+                    && record.close_reason.as_deref() == Some(close_reason_label(code)))
+        });
+        app_matches && reason_matches
+    }
+}
 
-        serde_json::to_writer(&mut file, &payload)
-            .map_err(|error| format!("could not encode log record: {error}"))?;
-        writeln!(file).map_err(|error| format!("could not write newline: {error}"))?;
+/// Reads the last `count` records matching `filter`, keeping only that many in
+/// memory. Without a filter just the trailing lines are parsed; with one, each
+/// line is parsed once and the matching records are kept.
+fn read_last_records(
+    path: &PathBuf,
+    count: usize,
+    filter: &TailFilter,
+) -> Result<Vec<LogRecord>, String> {
+    if !log_exists(path) || count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let reader = open_log_reader(path)?;
+
+    let mut lines: VecDeque<String> = VecDeque::new();
+    let mut records: VecDeque<LogRecord> = VecDeque::new();
+    for line in reader.lines() {
+        let line = line.map_err(|error| format!("could not read {}: {error}", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if filter.is_empty() {
+            if lines.len() == count {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        } else if let Some(record) = parse_record_line(&line)
+            && filter.matches(&record)
+        {
+            if records.len() == count {
+                records.pop_front();
+            }
+            records.push_back(record);
+        }
+    }
+
+    records.extend(lines.iter().filter_map(|line| parse_record_line(line)));
+    Ok(records.into())
+}
+
+fn parse_record_line(line: &str) -> Option<LogRecord> {
+    serde_json::from_str::<Value>(line)
+        .ok()
+        .and_then(|value| value_to_record(&value))
+}
+
+/// Case-insensitive `app_name` match; records without an app never match.
+fn record_matches_app(record: &LogRecord, app: &str) -> bool {
+    record
+        .app_name
+        .as_deref()
+        .is_some_and(|name| name.to_lowercase() == app.to_lowercase())
+}
+
+/// Opens a log for line reading, transparently decompressing gzip archives
+/// (detected by their magic bytes, not the extension).
+fn open_log_reader(path: &PathBuf) -> Result<Box<dyn BufRead>, String> {
+    if let Some(bytes) = stdin_log() {
+        return Ok(if bytes.starts_with(&GZIP_MAGIC) {
+            Box::new(BufReader::new(MultiGzDecoder::new(bytes)))
+        } else {
+            Box::new(bytes)
+        });
     }
+    let file =
+        File::open(path).map_err(|error| format!("could not open {}: {error}", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let is_gzip = reader
+        .fill_buf()
+        .map_err(|error| format!("could not read {}: {error}", path.display()))?
+        .starts_with(&GZIP_MAGIC);
+    if is_gzip {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
 
+/// Compressed archives are read-only; only the plain live log is written.
+fn ensure_plain_log(path: &PathBuf) -> Result<(), String> {
+    if stdin_log().is_some() {
+        return Err(String::from("--stdin logs can only be read"));
+    }
+    let mut magic = [0u8; 2];
+    let is_gzip = File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|()| magic == GZIP_MAGIC);
+    if is_gzip {
+        return Err(format!(
+            "{} is gzip-compressed and can only be read",
+            path.display()
+        ));
+    }
     Ok(())
 }
 
+/// Counts non-empty lines without decoding them.
+fn count_record_lines(path: &PathBuf) -> Result<usize, String> {
+    if !log_exists(path) {
+        return Ok(0);
+    }
+
+    let mut reader = open_log_reader(path)?;
+
+    let mut count = 0;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .map_err(|error| format!("could not read {}: {error}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Rewrites the log through `app_config::replace_file`, so a killed process
+/// leaves either the old or the new log, never a partial one.
+fn write_records(path: &PathBuf, records: &[LogRecord]) -> Result<(), String> {
+    ensure_plain_log(path)?;
+    app_config::replace_file(path, |file| {
+        for record in records {
+            serde_json::to_writer(&mut *file, &record_to_json(record))?;
+            writeln!(file)?;
+        }
+        Ok(())
+    })
+    .map_err(|error| format!("could not replace {}: {error}", path.display()))
+}
+
 fn value_to_record(value: &Value) -> Option<LogRecord> {
     let id = if let Some(id_u64) = value.get("id").and_then(Value::as_u64) {
         u32::try_from(id_u64).ok()?
@@ -922,6 +3489,7 @@ fn value_to_record(value: &Value) -> Option<LogRecord> {
     let close_reason = opt_non_empty(value.get("close_reason"));
     let closed_epoch = value.get("closed_epoch").and_then(Value::as_i64);
     let closed_hhmm = opt_non_empty(value.get("closed_hhmm"));
+    let seq = value.get("seq").and_then(Value::as_u64);
 
     Some(LogRecord {
         event_uid,
@@ -936,6 +3504,7 @@ fn value_to_record(value: &Value) -> Option<LogRecord> {
         close_reason,
         closed_epoch,
         closed_hhmm,
+        seq,
     })
 }
 
@@ -987,13 +3556,6 @@ fn parse_single_string_flag(args: &[String], flag: &str) -> Result<String, Strin
     }
 }
 
-fn parse_single_u32_flag(args: &[String], flag: &str) -> Result<u32, String> {
-    let value = parse_single_string_flag(args, flag)?;
-    value
-        .parse::<u32>()
-        .map_err(|_| format!("{flag} expects an integer"))
-}
-
 fn parse_single_u64_flag(args: &[String], flag: &str) -> Result<u64, String> {
     let value = parse_single_string_flag(args, flag)?;
     value
@@ -1010,7 +3572,57 @@ fn now_epoch() -> i64 {
 
 #[cfg(test)]
 mod tests {
-    use super::{extract_strings, split_body_fields};
+    use super::{
+        ExportCursor, LogRecord, TailFilter, TimeFormat, aggregate_records,
+        drop_notifications_older_than, encode_fields, extract_strings, latest_record,
+        markdown_table, merge_log_sources, merge_repeated, parse_app_filter, parse_close_reason,
+        purge_app_records, record_event_keys, record_json_schema, record_to_json,
+        split_body_fields, trim_records_per_app,
+    };
+
+    #[test]
+    fn markdown_table_escapes_pipes_and_line_breaks() {
+        let mut record = LogRecord::empty(3);
+        record.hhmm = Some(String::from("09:15"));
+        record.app_name = Some(String::from("Build | CI"));
+        record.summary = Some(String::from("Job failed\nsee log"));
+        record.body = Some(String::from("long body | never shown"));
+        let open = LogRecord::empty(4);
+
+        let table = markdown_table(&[&record, &open], TimeFormat::Logged);
+        assert_eq!(
+            table,
+            "| Time | App | Summary | Reason |\n\
+             | --- | --- | --- | --- |\n\
+             | 09:15 | Build \\| CI | Job failed see log | open |\n\
+             |  |  |  | open |\n"
+        );
+    }
+
+    #[test]
+    fn merge_repeated_collapses_only_consecutive_runs() {
+        let record = |id: u32, app: &str, summary: &str| {
+            let mut record = LogRecord::empty(id);
+            record.app_name = Some(app.to_string());
+            record.summary = Some(summary.to_string());
+            record
+        };
+        let newest = record(4, "Mail", "new emails");
+        let older = record(3, "Mail", "new emails");
+        let other = record(2, "Chat", "new emails");
+        let oldest = record(1, "Mail", "new emails");
+
+        let groups = merge_repeated(&[&newest, &older, &other, &oldest]);
+        let summary = groups
+            .iter()
+            .map(|(latest, earliest, count)| (latest.id, earliest.id, *count))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![(4, 3, 2), (2, 2, 1), (1, 1, 1)]);
+    }
+
+    fn monitor_lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
 
     #[test]
     fn extract_strings_keeps_multiline_body_before_actions() {
@@ -1035,10 +3647,312 @@ mod tests {
         assert_eq!(strings[3], "web.whatsapp.com\n\nhui");
     }
 
+    #[test]
+    fn extract_strings_unescapes_embedded_quotes() {
+        let block = monitor_lines(&[
+            "  MESSAGE \"susssasa{sv}i\" {",
+            "          STRING \"Terminal\";",
+            "          UINT32 0;",
+            "          STRING \"\";",
+            "          STRING \"Build \\\"release\\\" failed\";",
+            "          STRING \"path C:\\\\tmp\";",
+            "  };",
+        ]);
+
+        let strings = extract_strings(&block);
+        assert_eq!(strings[2], "Build \"release\" failed");
+        assert_eq!(strings[3], "path C:\\tmp");
+    }
+
+    #[test]
+    fn extract_strings_keeps_quotes_across_multiline_values() {
+        let block = monitor_lines(&[
+            "          STRING \"Mail\";",
+            "          UINT32 0;",
+            "          STRING \"\";",
+            "          STRING \"Re: \\\"lunch\\\"\";",
+            "          STRING \"she said \"ok\"",
+            "see you \\\"there\\\"\";",
+            "          ARRAY \"s\" {",
+            "          };",
+        ]);
+
+        let strings = extract_strings(&block);
+        assert_eq!(strings[2], "Re: \"lunch\"");
+        assert_eq!(strings[3], "she said \"ok\"\nsee you \"there\"");
+    }
+
     #[test]
     fn split_body_fields_extracts_source_and_content() {
         let (source, body) = split_body_fields("web.whatsapp.com\n\nTest");
         assert_eq!(source.as_deref(), Some("web.whatsapp.com"));
         assert_eq!(body.as_deref(), Some("Test"));
     }
+
+    #[test]
+    fn record_event_keys_attach_orphan_close_to_open_notification() {
+        let mut open = LogRecord::empty(5);
+        open.event_uid = Some(String::from("5_a"));
+        open.epoch = Some(1_000);
+        let mut orphan_close = LogRecord::empty(5);
+        orphan_close.close_reason_code = Some(1);
+        orphan_close.closed_epoch = Some(1_010);
+        let mut unrelated_close = LogRecord::empty(9);
+        unrelated_close.close_reason_code = Some(3);
+        unrelated_close.closed_epoch = Some(1_010);
+        let mut late_close = LogRecord::empty(5);
+        late_close.close_reason_code = Some(1);
+        late_close.closed_epoch = Some(1_020);
+
+        let keys = record_event_keys(&[open, orphan_close, unrelated_close, late_close]);
+        assert_eq!(keys[0], "5_a");
+        assert_eq!(keys[1], "5_a");
+        assert_eq!(keys[2], "legacy:9:2");
+        assert_eq!(keys[3], "legacy:5:3");
+    }
+
+    #[test]
+    fn drop_notifications_older_than_uses_newest_record_per_notification() {
+        let mut old_open = LogRecord::empty(1);
+        old_open.event_uid = Some(String::from("1_a"));
+        old_open.epoch = Some(100);
+        let mut recent_close = LogRecord::empty(1);
+        recent_close.event_uid = Some(String::from("1_a"));
+        recent_close.closed_epoch = Some(500);
+        let mut stale = LogRecord::empty(2);
+        stale.event_uid = Some(String::from("2_a"));
+        stale.epoch = Some(200);
+        let mut undated = LogRecord::empty(3);
+        undated.event_uid = Some(String::from("3_a"));
+
+        let kept = drop_notifications_older_than(vec![old_open, recent_close, stale, undated], 300);
+        let ids = kept.iter().map(|record| record.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 1, 3]);
+    }
+
+    #[test]
+    fn record_json_schema_lists_every_exported_field() {
+        let schema = record_json_schema();
+        let mut schema_fields = schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut record_fields = record_to_json(&LogRecord::empty(1))
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        schema_fields.sort();
+        record_fields.sort();
+        assert_eq!(schema_fields, record_fields);
+    }
+
+    #[test]
+    fn trim_records_per_app_keeps_newest_per_listed_app() {
+        let record = |id: u32, app: Option<&str>, epoch: i64| {
+            let mut record = LogRecord::empty(id);
+            record.event_uid = Some(format!("{id}_a"));
+            record.app_name = app.map(String::from);
+            record.epoch = Some(epoch);
+            record
+        };
+        let mut close = LogRecord::empty(1);
+        close.event_uid = Some(String::from("1_a"));
+        close.closed_epoch = Some(150);
+        let records = vec![
+            record(1, Some("Updates"), 100),
+            close,
+            record(2, Some("updates"), 200),
+            record(3, Some("Mail"), 50),
+            record(4, Some("Updates"), 300),
+            record(5, None, 10),
+        ];
+        let limits = [(String::from("updates"), 2)].into_iter().collect();
+
+        let kept = trim_records_per_app(records, &limits);
+        let ids = kept.iter().map(|record| record.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn purge_app_records_drops_matching_notifications_with_their_closes() {
+        let mut chatty = LogRecord::empty(1);
+        chatty.event_uid = Some(String::from("1_a"));
+        chatty.app_name = Some(String::from("Chatty"));
+        chatty.epoch = Some(100);
+        let mut chatty_close = LogRecord::empty(1);
+        chatty_close.event_uid = Some(String::from("1_a"));
+        chatty_close.close_reason_code = Some(1);
+        let mut mail = LogRecord::empty(2);
+        mail.event_uid = Some(String::from("2_a"));
+        mail.app_name = Some(String::from("Mail"));
+
+        let (kept, notifications) = purge_app_records(vec![chatty, chatty_close, mail], "chatty");
+        assert_eq!(notifications, 1);
+        let ids = kept.iter().map(|record| record.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![2]);
+    }
+
+    #[test]
+    fn tail_reason_filter_accepts_codes_and_labels() {
+        assert_eq!(parse_close_reason("expired"), Ok(1));
+        assert_eq!(parse_close_reason("Dismissed-By-User"), Ok(2));
+        assert_eq!(parse_close_reason("3"), Ok(3));
+        assert!(parse_close_reason("gone").is_err());
+
+        let filter = TailFilter {
+            app: None,
+            reason_code: Some(1),
+        };
+        let mut expired = LogRecord::empty(1);
+        expired.close_reason_code = Some(1);
+        let mut labelled = LogRecord::empty(2);
+        labelled.close_reason = Some(String::from("expired"));
+        let mut dismissed = LogRecord::empty(3);
+        dismissed.close_reason_code = Some(2);
+        assert!(filter.matches(&expired));
+        assert!(filter.matches(&labelled));
+        assert!(!filter.matches(&dismissed));
+        assert!(!filter.matches(&LogRecord::empty(4)));
+    }
+
+    #[test]
+    fn latest_record_matches_first_aggregated_record() {
+        let mut open = LogRecord::empty(1);
+        open.event_uid = Some(String::from("1_a"));
+        open.epoch = Some(100);
+        open.summary = Some(String::from("first"));
+        let mut newer = LogRecord::empty(2);
+        newer.event_uid = Some(String::from("2_a"));
+        newer.epoch = Some(150);
+        let mut close = LogRecord::empty(1);
+        close.event_uid = Some(String::from("1_a"));
+        close.close_reason_code = Some(1);
+        close.closed_epoch = Some(200);
+        let records = vec![open, newer, close];
+
+        let latest = latest_record(&records).unwrap();
+        let aggregated = aggregate_records(&records);
+        assert_eq!(record_to_json(&latest), record_to_json(&aggregated[0]));
+        assert_eq!(latest.summary.as_deref(), Some("first"));
+        assert!(latest_record(&[]).is_none());
+    }
+
+    #[test]
+    fn aggregate_records_orders_untimed_records_by_seq() {
+        let record = |uid: &str, seq: u64| {
+            let mut record = LogRecord::empty(1);
+            record.event_uid = Some(uid.to_string());
+            record.seq = Some(seq);
+            record
+        };
+        // File order disagrees with ingest order, as after a rewrite.
+        let records = vec![record("1_b", 7), record("1_a", 2), record("1_c", 4)];
+
+        let uids = aggregate_records(&records)
+            .into_iter()
+            .filter_map(|record| record.event_uid)
+            .collect::<Vec<_>>();
+        assert_eq!(uids, ["1_b", "1_c", "1_a"]);
+        let latest = latest_record(&records).unwrap();
+        assert_eq!(latest.event_uid.as_deref(), Some("1_b"));
+    }
+
+    #[test]
+    fn parse_app_filter_skips_comments_and_blank_lines() {
+        let text = "# noisy sources\nSlack\n\n  Spotify  \n#Firefox\nDiscord # not a comment\n";
+        assert_eq!(
+            parse_app_filter(text),
+            ["Slack", "Spotify", "Discord # not a comment"]
+        );
+    }
+
+    #[test]
+    fn merge_log_sources_takes_each_event_from_the_first_source() {
+        let record = |uid: Option<&str>, epoch: i64, summary: &str| {
+            let mut record = LogRecord::empty(1);
+            record.event_uid = uid.map(ToString::to_string);
+            record.epoch = Some(epoch);
+            record.summary = Some(summary.to_string());
+            record
+        };
+        let mut close = LogRecord::empty(1);
+        close.event_uid = Some(String::from("1_a"));
+        close.close_reason_code = Some(1);
+        close.closed_epoch = Some(300);
+        let laptop = vec![record(Some("1_a"), 200, "laptop"), close];
+        let desktop = vec![
+            record(Some("1_a"), 200, "desktop copy"),
+            record(Some("1_b"), 100, "desktop"),
+            record(None, 150, "legacy"),
+        ];
+
+        let (merged, skipped) = merge_log_sources(vec![laptop, desktop]);
+        let summaries = merged
+            .iter()
+            .map(|record| record.summary.as_deref().unwrap_or("close"))
+            .collect::<Vec<_>>();
+        assert_eq!(summaries, ["desktop", "legacy", "laptop", "close"]);
+        assert_eq!(skipped, 1);
+    }
+
+    #[test]
+    fn export_cursor_keeps_records_logged_later_in_the_same_second() {
+        let record = |uid: &str, epoch: i64| {
+            let mut record = LogRecord::empty(1);
+            record.event_uid = Some(uid.to_string());
+            record.epoch = Some(epoch);
+            record
+        };
+        let (older, first) = (record("1_a", 90), record("2_a", 100));
+        let cursor = ExportCursor::advance(None, &[&first, &older]).unwrap();
+        assert_eq!(ExportCursor::parse(&cursor.to_text()), Some(cursor.clone()));
+
+        let same_second = record("3_a", 100);
+        assert!(!cursor.is_new(&first) && !cursor.is_new(&older));
+        assert!(cursor.is_new(&same_second));
+
+        // A run that only finds `same_second` keeps `first` behind the cursor.
+        let cursor = ExportCursor::advance(Some(cursor), &[&same_second]).unwrap();
+        assert!(!cursor.is_new(&first) && !cursor.is_new(&same_second));
+        assert!(cursor.is_new(&record("4_a", 101)));
+        // Cursors written before event uids were stored still parse.
+        assert_eq!(ExportCursor::parse("100\n").unwrap().event_uids.len(), 0);
+    }
+
+    #[test]
+    fn extract_strings_keeps_body_lines_ending_in_quote_semicolon() {
+        let block = monitor_lines(&[
+            "          STRING \"Editor\";",
+            "          UINT32 0;",
+            "          STRING \"\";",
+            "          STRING \"Lint: 5\" screen\";",
+            "          STRING \"He wrote \"let x = 1\";",
+            "and left\";",
+            "          ARRAY \"s\" {",
+            "          };",
+        ]);
+
+        let strings = extract_strings(&block);
+        assert_eq!(strings[2], "Lint: 5\" screen");
+        assert_eq!(strings[3], "He wrote \"let x = 1\";\nand left");
+    }
+
+    #[test]
+    fn encode_fields_keeps_the_requested_key_order() {
+        let value = serde_json::json!({ "id": 7, "summary": "Hi", "app_name": "Mail" });
+        let fields = ["summary", "id", "app_name"].map(String::from);
+
+        assert_eq!(
+            encode_fields(&value, &fields, false).unwrap(),
+            r#"{"summary":"Hi","id":7,"app_name":"Mail"}"#
+        );
+        assert_eq!(
+            encode_fields(&value, &fields[..2], true).unwrap(),
+            "{\n  \"summary\": \"Hi\",\n  \"id\": 7\n}"
+        );
+    }
 }
@@ -1,28 +1,45 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::fs::{File, OpenOptions};
-use std::io::{self, BufRead, BufReader, Stdout, Write};
-use std::path::PathBuf;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Stdout, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use chrono::{DateTime, Local, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
 use crossterm::event::{
-    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
-    MouseEvent, MouseEventKind,
+    self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
 use crossterm::execute;
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
 };
+use crossterm::tty::IsTty;
+use flate2::bufread::MultiGzDecoder;
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 use serde_json::Value;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 mod app_config;
 
+use app_config::{KeyAction, KeyBinding};
+
 const AUTO_REFRESH_EVERY: Duration = Duration::from_secs(2);
+const DOUBLE_CLICK_WINDOW: Duration = Duration::from_millis(400);
+const ORPHAN_CLOSE_WINDOW_SECS: i64 = 24 * 60 * 60;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
 const DETAIL_INDENT: &str = "       ";
+const HIGHLIGHT_SYMBOL: &str = "  ";
+// Shorter queries match as plain case-insensitive substrings.
+const FUZZY_MIN_QUERY_CHARS: usize = 3;
+// Stored next to the log file.
+const TUI_STATE_FILE: &str = "tui_state.json";
 const STATUS_ICON_MISSED: &str = "";
 const STATUS_ICON_EMPTY: &str = "";
 const STATUS_ICON_ERROR: &str = "";
@@ -33,7 +50,10 @@ struct Notification {
     event_uid: Option<String>,
     summary: String,
     is_undismissed: bool,
+    close_reason_code: Option<u32>,
     time_hhmm: Option<String>,
+    epoch: Option<i64>,
+    closed_epoch: Option<i64>,
     app_name: Option<String>,
     body_source: Option<String>,
     body: Option<String>,
@@ -53,6 +73,7 @@ struct LogRecord {
     close_reason: Option<String>,
     closed_epoch: Option<i64>,
     closed_hhmm: Option<String>,
+    seq: Option<u64>,
 }
 
 impl LogRecord {
@@ -70,6 +91,7 @@ impl LogRecord {
             close_reason: None,
             closed_epoch: None,
             closed_hhmm: None,
+            seq: None,
         }
     }
 
@@ -107,17 +129,30 @@ impl LogRecord {
         if other.closed_hhmm.is_some() {
             self.closed_hhmm = other.closed_hhmm.clone();
         }
+        if other.seq.is_some() {
+            self.seq = other.seq;
+        }
     }
 }
 
 impl Notification {
+    /// Stable per-notification key for UI state; legacy records fall back to the id.
+    fn body_key(&self) -> String {
+        self.event_uid
+            .clone()
+            .unwrap_or_else(|| format!("id:{}", self.id))
+    }
+
     fn new(id: u32, summary: String) -> Self {
         Self {
             id,
             event_uid: None,
             summary,
             is_undismissed: false,
+            close_reason_code: None,
             time_hhmm: None,
+            epoch: None,
+            closed_epoch: None,
             app_name: None,
             body_source: None,
             body: None,
@@ -129,6 +164,7 @@ impl Notification {
 enum FilterMode {
     All,
     AutoDismissed,
+    Open,
 }
 
 impl FilterMode {
@@ -136,20 +172,86 @@ impl FilterMode {
         match self {
             Self::All => "history",
             Self::AutoDismissed => "missed",
+            Self::Open => "open",
         }
     }
 
+    fn from_label(label: &str) -> Option<Self> {
+        [Self::All, Self::AutoDismissed, Self::Open]
+            .into_iter()
+            .find(|mode| mode.label() == label)
+    }
+
     fn toggle(self) -> Self {
         match self {
-            Self::All => Self::AutoDismissed,
             Self::AutoDismissed => Self::All,
+            Self::All => Self::Open,
+            Self::Open => Self::AutoDismissed,
+        }
+    }
+}
+
+/// Which time orders the list: the last activity of a notification (its
+/// close, once closed) or when it arrived.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum SortOrder {
+    Closed,
+    Opened,
+}
+
+impl SortOrder {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Opened => "opened",
+        }
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        [Self::Closed, Self::Opened]
+            .into_iter()
+            .find(|order| order.label() == label)
+    }
+
+    fn toggle(self) -> Self {
+        match self {
+            Self::Closed => Self::Opened,
+            Self::Opened => Self::Closed,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum TimeDisplay {
+    Clock,
+    Relative,
+}
+
+impl TimeDisplay {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Clock => "clock",
+            Self::Relative => "relative",
+        }
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        [Self::Clock, Self::Relative]
+            .into_iter()
+            .find(|display| display.label() == label)
+    }
+
+    fn toggle(self) -> Self {
+        match self {
+            Self::Clock => Self::Relative,
+            Self::Relative => Self::Clock,
         }
     }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum CliMode {
-    Tui,
+    Tui { no_mouse: bool },
     Status { json: bool },
     Help,
 }
@@ -197,46 +299,321 @@ impl StatusSnapshot {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditField {
+    Summary,
+    Body,
+}
+
+/// The `e` prompt: new summary and body text for one notification, saved as
+/// an override record for its `event_uid`.
+#[derive(Debug, Clone)]
+struct EditInput {
+    event_uid: String,
+    id: u32,
+    field: EditField,
+    summary: String,
+    body: String,
+    body_source: Option<String>,
+    original_summary: String,
+    original_body: String,
+}
+
 struct App {
+    all_notifications: Vec<Notification>,
     notifications: Vec<Notification>,
+    missed_count: usize,
+    search_query: String,
+    search_input: bool,
+    // Digits typed at the `:` jump-to-id prompt while it is open.
+    id_input: Option<String>,
+    edit_input: Option<EditInput>,
+    // Opened on the first copy and kept so X11/Wayland can still serve the
+    // copied text while the TUI runs.
+    clipboard: Option<arboard::Clipboard>,
+    // Count typed before `j`/`k` (vim-style `10j`), cleared by any other key.
+    pending_count: Option<usize>,
     selected: usize,
+    list_offset: usize,
+    list_height: u16,
+    list_width: u16,
     filter: FilterMode,
+    today_only: bool,
+    today_start: Option<i64>,
+    time_display: TimeDisplay,
+    sort_order: SortOrder,
+    body_truncate_width: usize,
+    wrap_body: bool,
+    body_markup: app_config::BodyMarkup,
+    compact: bool,
+    // Blank rows between notifications outside compact mode.
+    item_spacing: u16,
+    // Non-selectable column labels above the list.
+    header: bool,
+    timezone: Option<Tz>,
+    // Closed at most this many seconds ago renders bold with a `*` (0 = off).
+    recent_close_secs: i64,
+    // Items whose body visibility is flipped from the mode default (space).
+    toggled_bodies: HashSet<String>,
+    visual: bool,
+    // Event uids marked in visual mode; `d`/`x` act on these when non-empty.
+    marked: HashSet<String>,
+    // Destructive action waiting for a second press of its key.
+    armed_action: Option<KeyAction>,
+    open_commands: HashMap<String, String>,
+    app_icons: HashMap<String, String>,
+    keymap: HashMap<KeyBinding, KeyAction>,
     status: String,
     should_quit: bool,
+    // Time and item of the last left click, to detect double-clicks.
+    last_click: Option<(Instant, usize)>,
+    // False until the first refresh has read the log.
+    loaded: bool,
     last_refresh: Instant,
+    log_path: PathBuf,
+    // Log mtime and size seen by the last refresh; the auto-refresh tick
+    // skips reloading while they are unchanged.
+    log_stamp: Option<(SystemTime, u64)>,
+    state_path: Option<PathBuf>,
 }
 
 impl App {
     fn new() -> Self {
+        let config = app_config::load_or_create();
         let mut app = Self {
+            all_notifications: Vec::new(),
             notifications: Vec::new(),
+            missed_count: 0,
+            search_query: String::new(),
+            search_input: false,
+            id_input: None,
+            edit_input: None,
+            clipboard: None,
+            pending_count: None,
             selected: 0,
+            list_offset: 0,
+            list_height: 0,
+            list_width: 0,
             filter: FilterMode::AutoDismissed,
+            today_only: false,
+            today_start: None,
+            time_display: TimeDisplay::Clock,
+            sort_order: SortOrder::Closed,
+            body_truncate_width: config.body_truncate_width,
+            wrap_body: config.wrap_body,
+            body_markup: config.body_markup,
+            compact: false,
+            item_spacing: config.item_spacing,
+            header: false,
+            timezone: config.timezone,
+            recent_close_secs: i64::try_from(config.recent_close_secs).unwrap_or(i64::MAX),
+            toggled_bodies: HashSet::new(),
+            visual: false,
+            marked: HashSet::new(),
+            armed_action: None,
+            open_commands: config.open_commands,
+            app_icons: config.app_icons,
+            keymap: build_keymap(&config.key_bindings),
             status: String::from("Loading notifications..."),
             should_quit: false,
+            last_click: None,
+            loaded: false,
             last_refresh: Instant::now(),
+            log_path: config.log_file_path.clone(),
+            log_stamp: None,
+            state_path: config
+                .log_file_path
+                .parent()
+                .map(|parent| parent.join(TUI_STATE_FILE)),
         };
-        app.refresh();
+        // The first refresh is left to `run_app`, after a "Loading" frame is
+        // on screen, so a large log never shows as a blank terminal.
+        app.load_view_state();
+        // An explicit start mode beats the one saved at the last exit.
+        if let Some(default_filter) = config.default_filter {
+            app.filter = match default_filter {
+                app_config::DefaultFilter::History => FilterMode::All,
+                app_config::DefaultFilter::Missed => FilterMode::AutoDismissed,
+                app_config::DefaultFilter::Open => FilterMode::Open,
+            };
+        }
         app
     }
 
+    /// `confirmed` is true when `action` was armed by the previous key press.
+    fn run_action(&mut self, action: KeyAction, confirmed: bool) {
+        match action {
+            KeyAction::Quit => self.should_quit = true,
+            KeyAction::Search => self.start_search(),
+            KeyAction::JumpToId => self.start_id_jump(),
+            KeyAction::Next => self.select_next(),
+            KeyAction::Prev => self.select_previous(),
+            KeyAction::First => self.select_first(),
+            KeyAction::Last => self.select_last(),
+            KeyAction::NextSameApp => self.select_same_app(true),
+            KeyAction::PrevSameApp => self.select_same_app(false),
+            KeyAction::PageDown => self.select_page_down(),
+            KeyAction::PageUp => self.select_page_up(),
+            KeyAction::ToggleFilter => self.toggle_filter(),
+            KeyAction::Dismiss => self.mark_selected_as_user_dismissed(),
+            KeyAction::DismissAll => self.dismiss_all_visible(confirmed),
+            KeyAction::Delete => self.delete_selected(confirmed),
+            KeyAction::Visual => self.toggle_visual(),
+            KeyAction::Copy => self.copy_selected_to_clipboard(),
+            KeyAction::Edit => self.start_edit(),
+            KeyAction::ToggleTime => self.toggle_time_display(),
+            KeyAction::ToggleSort => self.toggle_sort_order(),
+            KeyAction::Today => self.toggle_today(),
+            KeyAction::Compact => self.toggle_compact(),
+            KeyAction::ToggleHeader => self.header = !self.header,
+            // The body key marks items while in visual mode.
+            KeyAction::ToggleBody if self.visual => self.toggle_selected_mark(),
+            KeyAction::ToggleBody => self.toggle_selected_body(),
+            KeyAction::Refresh => self.refresh(),
+            KeyAction::Open => self.invoke_selected(),
+        }
+    }
+
+    /// Restores the filter and display toggles saved by `save_view_state`;
+    /// a missing or malformed state file leaves the defaults in place.
+    fn load_view_state(&mut self) {
+        let Some(state) = self
+            .state_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+        else {
+            return;
+        };
+
+        if let Some(filter) = state
+            .get("filter")
+            .and_then(Value::as_str)
+            .and_then(FilterMode::from_label)
+        {
+            self.filter = filter;
+        }
+        if let Some(time_display) = state
+            .get("time_display")
+            .and_then(Value::as_str)
+            .and_then(TimeDisplay::from_label)
+        {
+            self.time_display = time_display;
+        }
+        if let Some(sort_order) = state
+            .get("sort_order")
+            .and_then(Value::as_str)
+            .and_then(SortOrder::from_label)
+        {
+            self.sort_order = sort_order;
+        }
+        if let Some(today_only) = state.get("today_only").and_then(Value::as_bool) {
+            self.today_only = today_only;
+        }
+        if let Some(compact) = state.get("compact").and_then(Value::as_bool) {
+            self.compact = compact;
+        }
+        if let Some(header) = state.get("header").and_then(Value::as_bool) {
+            self.header = header;
+        }
+    }
+
+    fn save_view_state(&self) -> Result<(), String> {
+        let Some(path) = self.state_path.as_ref() else {
+            return Ok(());
+        };
+        let state = serde_json::json!({
+            "filter": self.filter.label(),
+            "time_display": self.time_display.label(),
+            "sort_order": self.sort_order.label(),
+            "today_only": self.today_only,
+            "compact": self.compact,
+            "header": self.header,
+        });
+        fs::write(path, format!("{state}\n"))
+            .map_err(|error| format!("could not write {}: {error}", path.display()))
+    }
+
+    /// Centered hint shown instead of an empty list.
+    fn empty_message(&self) -> String {
+        if !self.loaded {
+            return String::from("Loading notifications…");
+        }
+        if !self.search_query.is_empty() {
+            return format!("No notifications match \"{}\"", self.search_query);
+        }
+        let today = if self.today_only { " today" } else { "" };
+        match self.filter {
+            FilterMode::AutoDismissed => format!("No missed notifications{today}"),
+            FilterMode::Open => format!("No open notifications{today}"),
+            FilterMode::All if self.today_only => String::from("No notifications today"),
+            FilterMode::All => {
+                String::from("No history yet — start the logger with `notilog logger run`")
+            }
+        }
+    }
+
+    /// Auto-refresh tick: reloads only when the log's mtime or size changed.
+    fn refresh_if_changed(&mut self) {
+        // The today filter still needs a reload once midnight passes.
+        let day_changed =
+            self.today_only && local_day_start_epoch(self.timezone) != self.today_start;
+        if !day_changed
+            && self.log_stamp.is_some()
+            && log_file_stamp(&self.log_path) == self.log_stamp
+        {
+            self.last_refresh = Instant::now();
+            return;
+        }
+        self.refresh();
+    }
+
     fn refresh(&mut self) {
-        match fetch_notifications(self.filter) {
-            Ok(notifications) => {
-                self.notifications = notifications;
-                if self.notifications.is_empty() {
-                    self.selected = 0;
+        self.loaded = true;
+        // Taken before reading so a write racing the reload is picked up on
+        // the next tick.
+        self.log_stamp = log_file_stamp(&self.log_path);
+        let previous_event_uid = self
+            .selected_notification()
+            .and_then(|notification| notification.event_uid.clone());
+
+        if self.today_only {
+            self.today_start = local_day_start_epoch(self.timezone);
+        }
+
+        match fetch_notifications(self.filter, self.sort_order) {
+            Ok((notifications, missed_count)) => {
+                // Counted across all modes so the title shows it in history/open too.
+                self.missed_count = missed_count;
+                self.all_notifications = notifications;
+                self.apply_search(previous_event_uid);
+                self.status = if self.log_stamp.is_none() && !self.log_path.exists() {
+                    // An empty history would otherwise look like "Loaded 0".
+                    format!(
+                        "Log file not found at {} — is the logger running? Start it with `notilog logger run`",
+                        self.log_path.display()
+                    )
                 } else {
-                    self.selected = self.selected.min(self.notifications.len() - 1);
+                    format!(
+                        "Loaded {} notifications from {}",
+                        self.all_notifications.len(),
+                        self.filter.label()
+                    )
+                };
+                if self.today_only {
+                    self.status = match self.today_start {
+                        Some(_) => format!("{} | {} today", self.status, self.notifications.len()),
+                        None => format!("{} | could not determine today's date", self.status),
+                    };
+                }
+                if !self.search_query.is_empty() {
+                    self.status = format!("{} | {}", self.status, self.search_status());
                 }
-                self.status = format!(
-                    "Loaded {} notifications from {}",
-                    self.notifications.len(),
-                    self.filter.label()
-                );
             }
             Err(error) => {
+                self.all_notifications.clear();
                 self.notifications.clear();
+                self.missed_count = 0;
                 self.selected = 0;
                 self.status = format!("Failed to refresh: {error}");
             }
@@ -244,178 +621,832 @@ impl App {
         self.last_refresh = Instant::now();
     }
 
-    fn toggle_filter(&mut self) {
-        self.filter = self.filter.toggle();
-        self.refresh();
-    }
+    /// Rebuilds the visible list from `all_notifications`, ranking by fuzzy
+    /// score while a search query is set, and keeps the previous selection.
+    fn apply_search(&mut self, previous_event_uid: Option<String>) {
+        let candidates = self
+            .all_notifications
+            .iter()
+            .filter(|notification| self.within_day_filter(notification));
+        if self.search_query.is_empty() {
+            self.notifications = candidates.cloned().collect();
+        } else {
+            let matcher = SkimMatcherV2::default().ignore_case();
+            let mut scored = candidates
+                .filter_map(|notification| {
+                    search_score(&matcher, notification, &self.search_query)
+                        .map(|score| (score, notification.clone()))
+                })
+                .collect::<Vec<_>>();
+            scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            self.notifications = scored
+                .into_iter()
+                .map(|(_, notification)| notification)
+                .collect();
+        }
 
-    fn select_next(&mut self) {
-        if self.notifications.is_empty() {
-            return;
+        let restored = previous_event_uid.as_deref().and_then(|event_uid| {
+            self.notifications
+                .iter()
+                .position(|notification| notification.event_uid.as_deref() == Some(event_uid))
+        });
+        if let Some(index) = restored {
+            self.selected = index;
+        } else if self.notifications.is_empty() {
+            self.selected = 0;
+        } else {
+            self.selected = self.selected.min(self.notifications.len() - 1);
         }
-        self.selected = (self.selected + 1) % self.notifications.len();
     }
 
-    fn select_previous(&mut self) {
-        if self.notifications.is_empty() {
-            return;
+    /// Whether `notification` passes the "today" filter; everything passes
+    /// while it is off or the start of the local day is unknown.
+    fn within_day_filter(&self, notification: &Notification) -> bool {
+        match (self.today_only, self.today_start) {
+            (true, Some(start)) => notification.epoch.is_some_and(|epoch| epoch >= start),
+            _ => true,
         }
-        self.selected = if self.selected == 0 {
-            self.notifications.len() - 1
-        } else {
-            self.selected - 1
-        };
     }
 
-    fn select_first(&mut self) {
-        self.selected = 0;
+    fn search_status(&self) -> String {
+        format!(
+            "{} of {} match \"{}\"",
+            self.notifications.len(),
+            self.all_notifications.len(),
+            self.search_query
+        )
     }
 
-    fn select_last(&mut self) {
-        if !self.notifications.is_empty() {
-            self.selected = self.notifications.len() - 1;
+    fn start_search(&mut self) {
+        self.search_input = true;
+        self.status = self.search_status();
+    }
+
+    fn clear_search(&mut self) {
+        let previous_event_uid = self
+            .selected_notification()
+            .and_then(|notification| notification.event_uid.clone());
+        self.search_input = false;
+        self.search_query.clear();
+        self.apply_search(previous_event_uid);
+        self.status = String::from("Search cleared");
+    }
+
+    fn handle_search_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc => {
+                self.clear_search();
+                return;
+            }
+            KeyCode::Enter => {
+                self.search_input = false;
+                return;
+            }
+            KeyCode::Backspace => {
+                self.search_query.pop();
+            }
+            KeyCode::Char(ch) => self.search_query.push(ch),
+            _ => return,
         }
+        self.rerank_search();
     }
 
-    fn selected_notification(&self) -> Option<&Notification> {
-        self.notifications.get(self.selected)
+    /// Inserts pasted text into the query in one go; line breaks become
+    /// spaces since the query is a single line.
+    fn handle_search_paste(&mut self, text: &str) {
+        self.search_query.extend(
+            text.chars()
+                .map(|ch| if ch.is_control() { ' ' } else { ch }),
+        );
+        self.rerank_search();
     }
 
-    fn invoke_selected(&mut self) {
-        let Some(_notification) = self.selected_notification() else {
-            self.status = String::from("Nothing selected");
+    fn start_id_jump(&mut self) {
+        self.id_input = Some(String::new());
+    }
+
+    fn handle_id_key(&mut self, code: KeyCode) {
+        let Some(input) = self.id_input.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => self.id_input = None,
+            KeyCode::Enter => {
+                let input = self.id_input.take().unwrap_or_default();
+                self.jump_to_id(&input);
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(ch) if ch.is_ascii_digit() => input.push(ch),
+            _ => {}
+        }
+    }
+
+    /// Selects the next notification (after the current one, wrapping) whose
+    /// `id` matches; ids are reused by the daemon, so repeats cycle.
+    fn jump_to_id(&mut self, input: &str) {
+        let Ok(id) = input.parse::<u32>() else {
+            self.status = format!("Invalid id \"{input}\"");
             return;
         };
-        self.status = String::from("Open action is not available in log-only mode");
+        let count = self.notifications.len();
+        let found = (1..=count)
+            .map(|step| (self.selected + step) % count)
+            .find(|index| self.notifications[*index].id == id);
+        match found {
+            Some(index) => {
+                self.selected = index;
+                self.status = format!("Jumped to notification #{id}");
+            }
+            None => self.status = format!("Notification #{id} not found in this view"),
+        }
     }
 
-    fn mark_selected_as_user_dismissed(&mut self) {
+    fn start_edit(&mut self) {
         let Some(notification) = self.selected_notification() else {
             self.status = String::from("Nothing selected");
             return;
         };
+        let Some(event_uid) = notification.event_uid.clone() else {
+            self.status = String::from("Selected notification has no event id");
+            return;
+        };
+        let body = notification.body.clone().unwrap_or_default();
+        self.edit_input = Some(EditInput {
+            event_uid,
+            id: notification.id,
+            field: EditField::Summary,
+            summary: notification.summary.clone(),
+            body: body.clone(),
+            body_source: notification.body_source.clone(),
+            original_summary: notification.summary.clone(),
+            original_body: body,
+        });
+        self.status = String::from("Tab switches summary/body, Enter saves, Esc cancels");
+    }
 
-        if !notification.is_undismissed {
-            self.status = String::from("Selected notification is not auto-dismissed");
+    fn handle_edit_key(&mut self, code: KeyCode) {
+        let Some(edit) = self.edit_input.as_mut() else {
             return;
+        };
+        let text = match edit.field {
+            EditField::Summary => &mut edit.summary,
+            EditField::Body => &mut edit.body,
+        };
+        match code {
+            KeyCode::Esc => {
+                self.edit_input = None;
+                self.status = String::from("Edit cancelled");
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                edit.field = match edit.field {
+                    EditField::Summary => EditField::Body,
+                    EditField::Body => EditField::Summary,
+                };
+            }
+            KeyCode::Enter => {
+                if let Some(edit) = self.edit_input.take() {
+                    self.save_edit(&edit);
+                }
+            }
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Char(ch) => text.push(ch),
+            _ => {}
         }
+    }
 
-        let Some(event_uid) = notification.event_uid.as_deref() else {
-            self.status = String::from("Selected notification has no event id");
+    /// Pasted line breaks become spaces; both fields are single-line.
+    fn handle_edit_paste(&mut self, text: &str) {
+        let Some(edit) = self.edit_input.as_mut() else {
             return;
         };
+        let field = match edit.field {
+            EditField::Summary => &mut edit.summary,
+            EditField::Body => &mut edit.body,
+        };
+        field.extend(
+            text.chars()
+                .map(|ch| if ch.is_control() { ' ' } else { ch }),
+        );
+    }
 
-        match mark_notification_user_dismissed(event_uid) {
-            Ok(message) => {
-                self.status = message;
+    fn save_edit(&mut self, edit: &EditInput) {
+        match write_notification_edit(edit) {
+            Ok(true) => {
                 self.refresh();
+                self.status = format!("Saved edit to notification #{}", edit.id);
             }
-            Err(error) => {
-                self.status = format!("Failed to update dismiss reason: {error}");
-            }
+            Ok(false) => self.status = String::from("Nothing changed"),
+            Err(error) => self.status = format!("Failed to save edit: {error}"),
         }
     }
 
-    fn copy_selected_body_to_clipboard(&mut self) {
-        let Some(notification) = self.selected_notification() else {
+    fn rerank_search(&mut self) {
+        // Typing re-ranks from the top so the best match is selected.
+        self.apply_search(None);
+        self.selected = 0;
+        self.status = self.search_status();
+    }
+
+    fn toggle_filter(&mut self) {
+        self.filter = self.filter.toggle();
+        self.refresh();
+    }
+
+    fn toggle_today(&mut self) {
+        self.today_only = !self.today_only;
+        self.refresh();
+    }
+
+    fn toggle_compact(&mut self) {
+        self.compact = !self.compact;
+        self.toggled_bodies.clear();
+    }
+
+    /// Expands (or collapses) the selected item's body inline.
+    fn toggle_selected_body(&mut self) {
+        let Some(key) = self.selected_notification().map(Notification::body_key) else {
             self.status = String::from("Nothing selected");
             return;
         };
+        if !self.toggled_bodies.remove(&key) {
+            self.toggled_bodies.insert(key);
+        }
+    }
 
-        let Some(body) = notification
-            .body
-            .as_deref()
-            .map(str::trim)
-            .filter(|text| !text.is_empty())
-            .map(ToString::to_string)
-        else {
-            self.status = String::from("Selected notification has no body");
-            return;
+    /// Bodies show by default outside compact mode; space flips one item.
+    fn shows_body(&self, notification: &Notification) -> bool {
+        self.compact == self.toggled_bodies.contains(&notification.body_key())
+    }
+
+    fn toggle_time_display(&mut self) {
+        self.time_display = self.time_display.toggle();
+    }
+
+    fn toggle_sort_order(&mut self) {
+        self.sort_order = self.sort_order.toggle();
+        self.refresh();
+        self.status = match self.sort_order {
+            SortOrder::Closed => String::from("Sorted by last activity (close time)"),
+            SortOrder::Opened => String::from("Sorted by arrival (open time)"),
         };
+    }
 
-        match copy_text_to_clipboard(&body) {
-            Ok(backend) => {
-                self.status = format!("Copied body to clipboard via {backend}");
-            }
-            Err(error) => {
-                self.status = format!("Failed to copy body: {error}");
-            }
+    fn push_count_digit(&mut self, digit: u32) {
+        let count = self
+            .pending_count
+            .unwrap_or(0)
+            .saturating_mul(10)
+            .saturating_add(digit as usize);
+        self.pending_count = Some(count);
+        self.status = format!("Count: {count}");
+    }
+
+    /// Moves down one item, wrapping at the end; a pending count moves that
+    /// many items instead and stops at the last one.
+    fn select_next(&mut self) {
+        let count = self.pending_count.take();
+        if self.notifications.is_empty() {
+            return;
         }
+        self.selected = match count {
+            Some(count) => self
+                .selected
+                .saturating_add(count)
+                .min(self.notifications.len() - 1),
+            None => (self.selected + 1) % self.notifications.len(),
+        };
     }
-}
 
-fn main() -> io::Result<()> {
-    match parse_cli_mode() {
-        Ok(CliMode::Tui) => run_tui(),
-        Ok(CliMode::Status { json }) => {
-            print_status(json);
-            Ok(())
+    fn select_previous(&mut self) {
+        let count = self.pending_count.take();
+        if self.notifications.is_empty() {
+            return;
         }
-        Ok(CliMode::Help) => {
-            print_help();
-            Ok(())
+        self.selected = match count {
+            Some(count) => self.selected.saturating_sub(count),
+            None if self.selected == 0 => self.notifications.len() - 1,
+            None => self.selected - 1,
+        };
+    }
+
+    fn select_page_down(&mut self) {
+        let mut rows = 0u16;
+        let mut index = self.selected;
+        while index + 1 < self.notifications.len() {
+            let step = self.spacer_rows() + self.item_height(&self.notifications[index + 1]);
+            if index != self.selected && rows.saturating_add(step) > self.list_height {
+                break;
+            }
+            rows = rows.saturating_add(step);
+            index += 1;
         }
-        Err(error) => {
-            eprintln!("{error}");
-            eprintln!();
-            print_help();
-            std::process::exit(2);
+        self.selected = index;
+    }
+
+    fn select_page_up(&mut self) {
+        let mut rows = 0u16;
+        let mut index = self.selected;
+        while index > 0 {
+            let step = self.spacer_rows() + self.item_height(&self.notifications[index - 1]);
+            if index != self.selected && rows.saturating_add(step) > self.list_height {
+                break;
+            }
+            rows = rows.saturating_add(step);
+            index -= 1;
         }
+        self.selected = index;
     }
-}
 
-fn run_tui() -> io::Result<()> {
-    let mut terminal = setup_terminal()?;
-    let mut app = App::new();
-    let run_result = run_app(&mut terminal, &mut app);
-    let restore_result = restore_terminal(&mut terminal);
-    run_result?;
-    restore_result
-}
+    /// Width available to body text when wrapping is enabled.
+    fn body_wrap_width(&self) -> Option<usize> {
+        if !self.wrap_body {
+            return None;
+        }
+        let reserved = HIGHLIGHT_SYMBOL.len() + DETAIL_INDENT.len();
+        Some(usize::from(self.list_width).saturating_sub(reserved).max(1))
+    }
 
-fn parse_cli_mode() -> Result<CliMode, String> {
-    let mut args = env::args().skip(1);
-    let Some(command) = args.next() else {
-        return Ok(CliMode::Tui);
-    };
+    fn body_lines(&self, body: &str) -> Vec<Line<'static>> {
+        let styled = match self.body_markup {
+            app_config::BodyMarkup::Raw => body.chars().map(|ch| (ch, Style::new())).collect(),
+            app_config::BodyMarkup::Strip => parse_body_markup(body, false),
+            app_config::BodyMarkup::Render => parse_body_markup(body, true),
+        };
 
-    match command.as_str() {
-        "-h" | "--help" => {
-            if args.next().is_some() {
-                Err(String::from("help does not accept extra arguments"))
-            } else {
-                Ok(CliMode::Help)
+        let mut lines = Vec::new();
+        for source in styled.split(|(ch, _)| *ch == '\n') {
+            let text = source.iter().map(|(ch, _)| *ch).collect::<String>();
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let rendered = match self.body_wrap_width() {
+                Some(width) => wrap_text(text, width),
+                None => vec![truncate(text, self.body_truncate_width)],
+            };
+            // Wrapping and truncation keep non-space characters in order, so
+            // styles are matched back by walking those in step.
+            let mut styles = source
+                .iter()
+                .filter(|(ch, _)| !ch.is_whitespace())
+                .map(|(_, style)| *style);
+            let mut last_style = Style::new();
+            for line in rendered {
+                let mut spans: Vec<Span<'static>> = Vec::new();
+                let mut run = String::new();
+                let mut run_style = last_style;
+                for ch in line.chars() {
+                    let style = if ch.is_whitespace() {
+                        last_style
+                    } else {
+                        styles.next().unwrap_or(last_style)
+                    };
+                    if style != run_style && !run.is_empty() {
+                        spans.push(Span::styled(std::mem::take(&mut run), run_style));
+                    }
+                    run_style = style;
+                    last_style = style;
+                    run.push(ch);
+                }
+                if !run.is_empty() {
+                    spans.push(Span::styled(run, run_style));
+                }
+                lines.push(Line::from(spans));
             }
         }
-        "status" | "--status" | "-status" => parse_status_mode(args.collect()),
-        unknown => Err(format!("unknown argument: {unknown}")),
+        lines
     }
-}
 
-fn parse_status_mode(args: Vec<String>) -> Result<CliMode, String> {
-    let mut json = false;
+    /// Blank rows between notifications (`item_spacing`, none in compact mode).
+    fn spacer_rows(&self) -> u16 {
+        if self.compact { 0 } else { self.item_spacing }
+    }
 
-    for argument in args {
-        match argument.as_str() {
-            "--json" => json = true,
-            "-h" | "--help" => return Ok(CliMode::Help),
-            unknown => return Err(format!("unknown status argument: {unknown}")),
+    /// Spacer rows drawn after notification `index`: spacers only separate
+    /// notifications, so there is none after the last one.
+    fn spacer_after(&self, index: usize) -> u16 {
+        if index + 1 < self.notifications.len() {
+            self.spacer_rows()
+        } else {
+            0
         }
     }
 
-    Ok(CliMode::Status { json })
-}
+    fn item_height(&self, notification: &Notification) -> u16 {
+        if !self.shows_body(notification) {
+            return 1;
+        }
 
-fn print_help() {
-    println!("notitui - notification history TUI");
-    println!();
+        let source_lines = notification
+            .body_source
+            .as_deref()
+            .map(|source| usize::from(!source.trim().is_empty()))
+            .unwrap_or(0);
+        let body_lines = notification
+            .body
+            .as_deref()
+            .map(|body| self.body_lines(body).len())
+            .unwrap_or(0);
+
+        1 + u16::try_from(source_lines + body_lines).unwrap_or(u16::MAX - 1)
+    }
+
+    /// Moves `list_offset` just enough to keep the selected item fully visible
+    /// in a viewport of `height` rows.
+    fn scroll_to_selection(&mut self, height: u16) {
+        self.list_height = height;
+        if self.notifications.is_empty() {
+            self.list_offset = 0;
+            return;
+        }
+
+        if self.selected < self.list_offset {
+            self.list_offset = self.selected;
+            return;
+        }
+
+        while self.list_offset < self.selected {
+            let spacer = u32::from(self.spacer_rows());
+            let rows = self.notifications[self.list_offset..=self.selected]
+                .iter()
+                .map(|notification| u32::from(self.item_height(notification)) + spacer)
+                .sum::<u32>()
+                - spacer;
+            if rows <= u32::from(height) {
+                break;
+            }
+            self.list_offset += 1;
+        }
+    }
+
+    fn select_first(&mut self) {
+        self.selected = 0;
+    }
+
+    fn select_last(&mut self) {
+        if !self.notifications.is_empty() {
+            self.selected = self.notifications.len() - 1;
+        }
+    }
+
+    /// Moves to the next (or previous) notification from the selected app,
+    /// wrapping around the list like `select_next`/`select_previous`.
+    fn select_same_app(&mut self, forward: bool) {
+        let Some(app_name) = self
+            .selected_notification()
+            .and_then(|notification| notification.app_name.clone())
+        else {
+            self.status = String::from("Selected notification has no app name");
+            return;
+        };
+
+        let len = self.notifications.len();
+        let found = (1..len)
+            .map(|step| {
+                if forward {
+                    (self.selected + step) % len
+                } else {
+                    (self.selected + len - step) % len
+                }
+            })
+            .find(|&index| self.notifications[index].app_name.as_deref() == Some(&app_name));
+        match found {
+            Some(index) => self.selected = index,
+            None => self.status = format!("No other notifications from {app_name}"),
+        }
+    }
+
+    fn selected_notification(&self) -> Option<&Notification> {
+        self.notifications.get(self.selected)
+    }
+
+    /// The `[app_icons]` glyph for a notification's app, else the `*` entry.
+    fn app_icon(&self, notification: &Notification) -> Option<&str> {
+        notification
+            .app_name
+            .as_deref()
+            .and_then(|app_name| self.app_icons.get(&app_name.trim().to_lowercase()))
+            .or_else(|| self.app_icons.get("*"))
+            .map(String::as_str)
+    }
+
+    fn invoke_selected(&mut self) {
+        let Some(notification) = self.selected_notification() else {
+            self.status = String::from("Nothing selected");
+            return;
+        };
+
+        let Some((app_name, command)) = notification.app_name.as_deref().and_then(|app_name| {
+            self.open_commands
+                .get(&app_name.trim().to_lowercase())
+                .map(|command| (app_name.to_string(), command.clone()))
+        }) else {
+            self.status = match notification.app_name.as_deref() {
+                Some(app_name) => format!("No [open_commands] entry for {app_name}"),
+                None => {
+                    String::from("No [open_commands] entry for a notification without an app name")
+                }
+            };
+            return;
+        };
+
+        self.status = match spawn_open_command(&command) {
+            Ok(()) => format!("Opened {app_name} ({command})"),
+            Err(error) => format!("Failed to open {app_name}: {error}"),
+        };
+    }
+
+    /// The key to show for `action` in hints: its shortest binding (lowercase
+    /// first), or the action's `[keys]` name when nothing is bound to it.
+    fn key_hint(&self, action: KeyAction) -> String {
+        self.first_key(action)
+            .unwrap_or_else(|| format!("<{}>", action.name()))
+    }
+
+    fn first_key(&self, action: KeyAction) -> Option<String> {
+        self.keymap
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(binding, _)| key_label(*binding))
+            .min_by_key(|label| {
+                (
+                    label.chars().count(),
+                    label.to_lowercase() != *label,
+                    label.clone(),
+                )
+            })
+    }
+
+    fn toggle_visual(&mut self) {
+        self.visual = !self.visual;
+        self.marked.clear();
+        self.status = if self.visual {
+            format!(
+                "Visual mode: {} marks, {} dismisses, {} deletes, {}/Esc exits",
+                self.key_hint(KeyAction::ToggleBody),
+                self.key_hint(KeyAction::Dismiss),
+                self.key_hint(KeyAction::Delete),
+                self.key_hint(KeyAction::Visual),
+            )
+        } else {
+            String::from("Left visual mode")
+        };
+    }
+
+    fn toggle_selected_mark(&mut self) {
+        let Some(event_uid) = self
+            .selected_notification()
+            .and_then(|notification| notification.event_uid.clone())
+        else {
+            self.status = String::from("Selected notification has no event id");
+            return;
+        };
+        if !self.marked.remove(&event_uid) {
+            self.marked.insert(event_uid);
+        }
+        self.status = format!("{} marked", self.marked.len());
+    }
+
+    /// Event uids a bulk action applies to: the marked set, or the cursor item.
+    fn action_targets(&self) -> Vec<String> {
+        if !self.marked.is_empty() {
+            return self
+                .notifications
+                .iter()
+                .filter_map(|notification| notification.event_uid.clone())
+                .filter(|event_uid| self.marked.contains(event_uid))
+                .collect();
+        }
+        self.selected_notification()
+            .and_then(|notification| notification.event_uid.clone())
+            .into_iter()
+            .collect()
+    }
+
+    fn finish_bulk_action(&mut self, message: String) {
+        self.visual = false;
+        self.marked.clear();
+        self.refresh();
+        self.status = message;
+    }
+
+    fn mark_selected_as_user_dismissed(&mut self) {
+        if self.marked.is_empty() {
+            let Some(notification) = self.selected_notification() else {
+                self.status = String::from("Nothing selected");
+                return;
+            };
+            if !notification.is_undismissed {
+                self.status = String::from("Selected notification is not auto-dismissed");
+                return;
+            }
+        }
+
+        let targets = self.action_targets();
+        if targets.is_empty() {
+            self.status = String::from("Selected notification has no event id");
+            return;
+        }
+
+        match mark_notifications_user_dismissed(&targets) {
+            Ok(message) => self.finish_bulk_action(message),
+            Err(error) => {
+                self.status = format!("Failed to update dismiss reason: {error}");
+            }
+        }
+    }
+
+    /// Marks every displayed auto-dismissed notification (the current mode,
+    /// today filter, and search) as dismissed-by-user after a second `D`.
+    fn dismiss_all_visible(&mut self, confirmed: bool) {
+        let targets = self
+            .notifications
+            .iter()
+            .filter(|notification| notification.is_undismissed)
+            .filter_map(|notification| notification.event_uid.clone())
+            .collect::<Vec<_>>();
+        if targets.is_empty() {
+            self.status = String::from("No auto-dismissed notifications shown");
+            return;
+        }
+        if !confirmed {
+            self.armed_action = Some(KeyAction::DismissAll);
+            self.status = format!(
+                "Press {} again to mark {} shown notification(s) as dismissed-by-user",
+                self.key_hint(KeyAction::DismissAll),
+                targets.len()
+            );
+            return;
+        }
+
+        match mark_notifications_user_dismissed(&targets) {
+            Ok(message) => self.finish_bulk_action(message),
+            Err(error) => {
+                self.status = format!("Failed to update dismiss reason: {error}");
+            }
+        }
+    }
+
+    /// Deletes the marked (or selected) notifications after a second `x`.
+    fn delete_selected(&mut self, confirmed: bool) {
+        let targets = self.action_targets();
+        if targets.is_empty() {
+            self.status = String::from("Nothing selected");
+            return;
+        }
+        if !confirmed {
+            self.armed_action = Some(KeyAction::Delete);
+            self.status = format!(
+                "Press {} again to delete {} notification(s) from the log",
+                self.key_hint(KeyAction::Delete),
+                targets.len()
+            );
+            return;
+        }
+
+        match delete_notifications(&targets) {
+            Ok(removed) => {
+                self.finish_bulk_action(format!("Deleted {removed} notification(s) from the log"))
+            }
+            Err(error) => {
+                self.status = format!("Failed to delete: {error}");
+            }
+        }
+    }
+
+    fn copy_selected_to_clipboard(&mut self) {
+        let Some(notification) = self.selected_notification() else {
+            self.status = String::from("Nothing selected");
+            return;
+        };
+
+        let body = notification
+            .body
+            .as_deref()
+            .map(str::trim)
+            .filter(|text| !text.is_empty());
+        let (label, text) = match body {
+            Some(body) => ("body", body.to_string()),
+            None => ("summary", notification.summary.trim().to_string()),
+        };
+        if text.is_empty() {
+            self.status = String::from("Selected notification has no text to copy");
+            return;
+        }
+
+        match self.copy_text_to_clipboard(&text) {
+            Ok(()) => {
+                self.status = format!("Copied {label} to clipboard");
+            }
+            Err(error) => {
+                self.status = format!("Failed to copy {label}: {error}");
+            }
+        }
+    }
+
+    fn copy_text_to_clipboard(&mut self, text: &str) -> Result<(), String> {
+        let clipboard = match &mut self.clipboard {
+            Some(clipboard) => clipboard,
+            None => self.clipboard.insert(
+                arboard::Clipboard::new()
+                    .map_err(|error| format!("no clipboard available: {error}"))?,
+            ),
+        };
+        clipboard.set_text(text).map_err(|error| error.to_string())
+    }
+}
+
+fn main() -> io::Result<()> {
+    match parse_cli_mode() {
+        Ok(CliMode::Tui { no_mouse }) => {
+            if let Err(error) = run_tui(no_mouse) {
+                eprintln!("{error}");
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Ok(CliMode::Status { json }) => {
+            print_status(json);
+            Ok(())
+        }
+        Ok(CliMode::Help) => {
+            print_help();
+            Ok(())
+        }
+        Err(error) => {
+            eprintln!("{error}");
+            eprintln!();
+            print_help();
+            std::process::exit(2);
+        }
+    }
+}
+
+fn run_tui(no_mouse: bool) -> io::Result<()> {
+    let mouse_capture = !no_mouse && app_config::load_or_create().mouse_capture;
+    let mut terminal = setup_terminal(mouse_capture)?;
+    let mut app = App::new();
+    let run_result = run_app(&mut terminal, &mut app);
+    let restore_result = restore_terminal(&mut terminal, mouse_capture);
+    if let Err(error) = app.save_view_state() {
+        eprintln!("Failed to save view state: {error}");
+    }
+    run_result?;
+    restore_result
+}
+
+fn parse_cli_mode() -> Result<CliMode, String> {
+    let mut args = env::args().skip(1);
+    let Some(command) = args.next() else {
+        return Ok(CliMode::Tui { no_mouse: false });
+    };
+
+    match command.as_str() {
+        "-h" | "--help" => {
+            if args.next().is_some() {
+                Err(String::from("help does not accept extra arguments"))
+            } else {
+                Ok(CliMode::Help)
+            }
+        }
+        "status" | "--status" | "-status" => parse_status_mode(args.collect()),
+        "--no-mouse" => match args.next() {
+            Some(extra) => Err(format!("unknown argument: {extra}")),
+            None => Ok(CliMode::Tui { no_mouse: true }),
+        },
+        unknown => Err(format!("unknown argument: {unknown}")),
+    }
+}
+
+fn parse_status_mode(args: Vec<String>) -> Result<CliMode, String> {
+    let mut json = false;
+
+    for argument in args {
+        match argument.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => return Ok(CliMode::Help),
+            unknown => return Err(format!("unknown status argument: {unknown}")),
+        }
+    }
+
+    Ok(CliMode::Status { json })
+}
+
+fn print_help() {
+    println!("notitui - notification history TUI");
+    println!();
     println!("Usage:");
-    println!("  notitui");
+    println!("  notitui [--no-mouse]");
     println!("  notitui --status [--json]");
     println!("  notitui status [--json]");
     println!();
     println!("Options:");
     println!("  -h, --help       Show this help");
+    println!("  --no-mouse       Leave the mouse to the terminal (native text selection)");
     println!("  --status         Print status for bars/scripts and exit");
     println!("  --json           Print status as JSON (for Waybar return-type=json)");
 }
@@ -475,36 +1506,135 @@ fn print_status_json(text: &str, class: &str, tooltip: &str, missed: usize, hist
 }
 
 fn fetch_status_snapshot() -> Result<StatusSnapshot, String> {
-    let notifications = fetch_notifications(FilterMode::All)?;
-    let missed_count = notifications
-        .iter()
-        .filter(|notification| notification.is_undismissed)
-        .count();
+    let (notifications, missed_count) = fetch_notifications(FilterMode::All, SortOrder::Closed)?;
     Ok(StatusSnapshot {
         missed_count,
         history_count: notifications.len(),
     })
 }
 
-fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+/// Built-in key bindings, in `app_config::parse_key_spec` syntax.
+const DEFAULT_KEYS: &[(KeyAction, &[&str])] = &[
+    (KeyAction::Quit, &["q", "esc"]),
+    (KeyAction::Search, &["/"]),
+    (KeyAction::JumpToId, &[":"]),
+    (KeyAction::Next, &["j", "down"]),
+    (KeyAction::Prev, &["k", "up"]),
+    (KeyAction::First, &["g"]),
+    (KeyAction::Last, &["G"]),
+    (KeyAction::NextSameApp, &["n"]),
+    (KeyAction::PrevSameApp, &["N"]),
+    (KeyAction::PageDown, &["pagedown"]),
+    (KeyAction::PageUp, &["pageup"]),
+    (KeyAction::ToggleFilter, &["f", "F"]),
+    (KeyAction::Dismiss, &["d"]),
+    (KeyAction::DismissAll, &["D"]),
+    (KeyAction::Delete, &["x"]),
+    (KeyAction::Visual, &["v"]),
+    (KeyAction::Copy, &["y"]),
+    (KeyAction::Edit, &["e"]),
+    (KeyAction::ToggleTime, &["t"]),
+    (KeyAction::ToggleSort, &["o"]),
+    (KeyAction::Today, &["1"]),
+    (KeyAction::Compact, &["c"]),
+    (KeyAction::ToggleHeader, &["H"]),
+    (KeyAction::ToggleBody, &["space"]),
+    (KeyAction::Refresh, &["r"]),
+    (KeyAction::Open, &["enter"]),
+];
+
+/// Key lookup table: defaults for actions not rebound in `[keys]`, then the
+/// configured keys, which win over a default on the same key.
+fn build_keymap(overrides: &HashMap<KeyAction, Vec<KeyBinding>>) -> HashMap<KeyBinding, KeyAction> {
+    let mut keymap = HashMap::new();
+    for (action, specs) in DEFAULT_KEYS {
+        if overrides.contains_key(action) {
+            continue;
+        }
+        for binding in specs
+            .iter()
+            .filter_map(|spec| app_config::parse_key_spec(spec))
+        {
+            keymap.insert(binding, *action);
+        }
+    }
+    for (action, bindings) in overrides {
+        for binding in bindings {
+            keymap.insert(*binding, *action);
+        }
+    }
+    keymap
+}
+
+/// Normalizes a key event for keymap lookup; Shift is dropped because it is
+/// already part of the character (`G`).
+fn key_binding(key: KeyEvent) -> KeyBinding {
+    (
+        key.code,
+        key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+    )
+}
+
+/// A binding as the legend shows it, e.g. `j`, `Space`, `PgDn`, `C-n`.
+fn key_label((code, modifiers): KeyBinding) -> String {
+    let key = match code {
+        KeyCode::Char(' ') => String::from("Space"),
+        KeyCode::Char(ch) => ch.to_string(),
+        KeyCode::F(number) => format!("F{number}"),
+        KeyCode::PageUp => String::from("PgUp"),
+        KeyCode::PageDown => String::from("PgDn"),
+        KeyCode::Delete => String::from("Del"),
+        other => format!("{other:?}"),
+    };
+    let mut label = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        label.push_str("C-");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        label.push_str("A-");
+    }
+    label + &key
+}
+
+fn setup_terminal(mouse_capture: bool) -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+    // Raw mode and the alternate screen need a real terminal on both ends;
+    // piped or redirected runs would fail cryptically or print escape codes.
+    if !io::stdin().is_tty() || !io::stdout().is_tty() {
+        return Err(io::Error::other(
+            "notitui needs an interactive terminal. For scripts and pipes use `notitui --status [--json]` or `notilog export`/`notilog query`.",
+        ));
+    }
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
+    if mouse_capture {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     Terminal::new(backend)
 }
 
-fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> io::Result<()> {
+fn restore_terminal(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    mouse_capture: bool,
+) -> io::Result<()> {
     disable_raw_mode()?;
+    if mouse_capture {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
     execute!(
         terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableBracketedPaste,
+        LeaveAlternateScreen
     )?;
     terminal.show_cursor()
 }
 
 fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) -> io::Result<()> {
+    if !app.loaded {
+        terminal.draw(|frame| render_ui(frame, app))?;
+        app.refresh();
+    }
     loop {
         terminal.draw(|frame| render_ui(frame, app))?;
 
@@ -519,218 +1649,961 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
                         continue;
                     }
 
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => app.should_quit = true,
-                        KeyCode::Down | KeyCode::Char('j') => app.select_next(),
-                        KeyCode::Up | KeyCode::Char('k') => app.select_previous(),
-                        KeyCode::Char('g') => app.select_first(),
-                        KeyCode::Char('G') => app.select_last(),
-                        KeyCode::Char('f') | KeyCode::Char('F') => app.toggle_filter(),
-                        KeyCode::Char('d') => app.mark_selected_as_user_dismissed(),
-                        KeyCode::Char('y') => app.copy_selected_body_to_clipboard(),
-                        KeyCode::Char('r') => app.refresh(),
-                        KeyCode::Enter => app.invoke_selected(),
-                        _ => {}
-                    }
-                }
-                Event::Mouse(mouse) => {
-                    let size = terminal.size()?;
-                    let area = Rect::new(0, 0, size.width, size.height);
-                    handle_mouse_event(app, mouse, area);
-                }
-                _ => {}
+                    if app.search_input {
+                        app.handle_search_key(key.code);
+                        continue;
+                    }
+                    if app.id_input.is_some() {
+                        app.handle_id_key(key.code);
+                        continue;
+                    }
+                    if app.edit_input.is_some() {
+                        app.handle_edit_key(key.code);
+                        continue;
+                    }
+
+                    // Digits 2-9 start a count for `j`/`k` (`1` is the today
+                    // filter); a digit bound to an action only continues a
+                    // count that is already pending.
+                    let binding = key_binding(key);
+                    if let KeyCode::Char(ch) = key.code
+                        && let Some(digit) = ch.to_digit(10)
+                        && binding.1.is_empty()
+                        && (app.pending_count.is_some()
+                            || ((2..=9).contains(&digit) && !app.keymap.contains_key(&binding)))
+                    {
+                        app.push_count_digit(digit);
+                        continue;
+                    }
+
+                    let armed = app.armed_action.take();
+                    // Esc always backs out of a count, visual mode, and
+                    // searches first, whatever `quit` is bound to.
+                    if key.code == KeyCode::Esc && app.pending_count.is_some() {
+                        app.status = String::from("Count cleared");
+                    } else if key.code == KeyCode::Esc && app.visual {
+                        app.toggle_visual();
+                    } else if key.code == KeyCode::Esc && !app.search_query.is_empty() {
+                        app.clear_search();
+                    } else if let Some(action) = app.keymap.get(&binding).copied() {
+                        app.run_action(action, armed == Some(action));
+                    }
+                    app.pending_count = None;
+                }
+                Event::Mouse(mouse) => {
+                    let size = terminal.size()?;
+                    let area = Rect::new(0, 0, size.width, size.height);
+                    handle_mouse_event(app, mouse, area);
+                }
+                // Paste only feeds the search and edit prompts; elsewhere it
+                // is ignored.
+                Event::Paste(text) if app.search_input => app.handle_search_paste(&text),
+                Event::Paste(text) if app.edit_input.is_some() => app.handle_edit_paste(&text),
+                Event::Resize(_, _) => {
+                    // Drop the old buffer so the next draw repaints every cell at
+                    // the new size (and updates list_offset before any click).
+                    terminal.autoresize()?;
+                    terminal.clear()?;
+                }
+                _ => {}
+            }
+        } else if app.last_refresh.elapsed() >= AUTO_REFRESH_EVERY {
+            app.refresh_if_changed();
+        }
+    }
+}
+
+fn handle_mouse_event(app: &mut App, mouse: MouseEvent, terminal_area: Rect) {
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            let Some(index) = select_notification_at(app, mouse.column, mouse.row, terminal_area)
+            else {
+                app.last_click = None;
+                return;
+            };
+            let now = Instant::now();
+            match app.last_click {
+                Some((at, previous))
+                    if previous == index && now.duration_since(at) <= DOUBLE_CLICK_WINDOW =>
+                {
+                    app.last_click = None;
+                    app.toggle_selected_body();
+                }
+                _ => app.last_click = Some((now, index)),
+            }
+        }
+        MouseEventKind::ScrollDown => app.select_next(),
+        MouseEventKind::ScrollUp => app.select_previous(),
+        _ => {}
+    }
+}
+
+/// Selects the notification under the cursor and returns its index, or
+/// `None` when the click missed every item.
+fn select_notification_at(
+    app: &mut App,
+    column: u16,
+    row: u16,
+    terminal_area: Rect,
+) -> Option<usize> {
+    if app.notifications.is_empty() {
+        return None;
+    }
+
+    let list_inner = list_inner_area(terminal_area, app.compact, app.header);
+    if list_inner.width == 0 || list_inner.height == 0 {
+        return None;
+    }
+    if column < list_inner.x
+        || column >= list_inner.x + list_inner.width
+        || row < list_inner.y
+        || row >= list_inner.y + list_inner.height
+    {
+        return None;
+    }
+
+    let mut y = row - list_inner.y;
+    for (idx, notification) in app.notifications.iter().enumerate().skip(app.list_offset) {
+        let item_height = app.item_height(notification);
+        if y < item_height {
+            app.selected = idx;
+            return Some(idx);
+        }
+        y -= item_height;
+
+        let spacer = app.spacer_after(idx);
+        if spacer > 0 {
+            // Spacer rows between notifications.
+            if y < spacer {
+                return None;
+            }
+            y -= spacer;
+        }
+    }
+    None
+}
+
+/// `ListItem` index of notification `index`: each notification is one item,
+/// followed by one spacer item (whatever its height) when `spacer_rows > 0`.
+/// The header row is drawn outside the list, so it does not count.
+fn visual_index_for(index: usize, spacer_rows: u16) -> usize {
+    if spacer_rows > 0 { index * 2 } else { index }
+}
+
+/// Splits the screen into list, status line, and legend areas.
+fn screen_chunks(terminal_area: Rect) -> std::rc::Rc<[Rect]> {
+    let area = terminal_area.inner(Margin {
+        horizontal: 1,
+        vertical: 1,
+    });
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ])
+        .split(area)
+}
+
+/// Rows available to list items, below the header row when it is shown.
+fn list_inner_area(terminal_area: Rect, compact: bool, header: bool) -> Rect {
+    let chunks = screen_chunks(terminal_area);
+
+    let inner = Block::bordered()
+        .padding(list_padding(compact))
+        .inner(chunks[0]);
+    split_list_header(inner, header).1
+}
+
+/// Takes the first row of `inner` for the column header when `header` is on.
+fn split_list_header(inner: Rect, header: bool) -> (Option<Rect>, Rect) {
+    if !header || inner.height == 0 {
+        return (None, inner);
+    }
+    let header_area = Rect { height: 1, ..inner };
+    let items_area = Rect {
+        y: inner.y + 1,
+        height: inner.height - 1,
+        ..inner
+    };
+    (Some(header_area), items_area)
+}
+
+/// Blank row above the first notification, dropped in compact mode.
+fn list_padding(compact: bool) -> Padding {
+    Padding::new(0, 0, u16::from(!compact), 0)
+}
+
+fn render_ui(frame: &mut Frame, app: &mut App) {
+    let chunks = screen_chunks(frame.area());
+
+    let title = format!(
+        " Notifications | mode: {}{}{} | count: {} | missed: {}{} ",
+        app.filter.label(),
+        if app.today_only { " (today)" } else { "" },
+        if app.sort_order == SortOrder::Opened {
+            " (by arrival)"
+        } else {
+            ""
+        },
+        app.notifications.len(),
+        app.missed_count,
+        if app.visual {
+            format!(" | visual: {} marked", app.marked.len())
+        } else {
+            String::new()
+        }
+    );
+    let now_utc = Utc::now();
+    let refreshed_at = TimeDelta::from_std(app.last_refresh.elapsed())
+        .map(|elapsed| now_utc - elapsed)
+        .unwrap_or(now_utc);
+    let clock = format!(
+        " refreshed {} | {} ",
+        format_in_zone(refreshed_at, app.timezone, "%H:%M:%S"),
+        format_in_zone(now_utc, app.timezone, "%H:%M")
+    );
+    let list_block = Block::bordered()
+        .title(title)
+        .title(Line::from(clock).right_aligned())
+        .border_style(Style::new().fg(Color::Green))
+        .padding(list_padding(app.compact));
+    let (header_area, list_inner) = split_list_header(list_block.inner(chunks[0]), app.header);
+    app.list_width = list_inner.width;
+
+    let now = now_epoch();
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let mut items: Vec<ListItem> = Vec::new();
+    for (idx, notification) in app.notifications.iter().enumerate() {
+        let mut lines = Vec::new();
+        let summary_color = if notification.is_undismissed {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+        let time_prefix = match app.time_display {
+            TimeDisplay::Clock => {
+                // With a configured zone, re-render from the epoch instead of the
+                // logger host's stored HH:MM.
+                let zoned = app.timezone.and_then(|timezone| {
+                    let time = DateTime::from_timestamp(notification.epoch?, 0)?;
+                    Some(format_in_zone(time, Some(timezone), "%H:%M"))
+                });
+                match zoned.as_deref().or(notification.time_hhmm.as_deref()) {
+                    Some(time) if !time.is_empty() => format!("{time}  "),
+                    _ => String::new(),
+                }
+            }
+            TimeDisplay::Relative => {
+                let age = notification
+                    .epoch
+                    .map(|epoch| format_relative_age(now - epoch))
+                    .unwrap_or_else(|| String::from("--"));
+                format!("{age}  ")
+            }
+        };
+        let query = app.search_query.as_str();
+        let badge = close_reason_badge(notification.close_reason_code);
+        let recently_closed = app.recent_close_secs > 0
+            && notification
+                .closed_epoch
+                .is_some_and(|closed| now - closed <= app.recent_close_secs);
+        let mut spans = vec![Span::raw(format!(
+            "{badge}{}",
+            if recently_closed { "*" } else { " " }
+        ))];
+        if let Some(icon) = app.app_icon(notification) {
+            spans.push(Span::raw(format!("{icon} ")));
+        }
+        if let Some(app_name) = notification.app_name.as_deref() {
+            let app_style = Style::new().fg(Color::Gray).add_modifier(Modifier::DIM);
+            spans.push(Span::styled("[", app_style));
+            spans.extend(highlight_matches(app_name, query, &matcher, app_style));
+            spans.push(Span::styled("] ", app_style));
+        }
+        spans.push(Span::raw(time_prefix));
+        spans.extend(highlight_matches(
+            &notification.summary,
+            query,
+            &matcher,
+            Style::new(),
+        ));
+        let mut summary_style = Style::new().fg(summary_color);
+        if recently_closed {
+            summary_style = summary_style.add_modifier(Modifier::BOLD);
+        }
+        lines.push(Line::from(spans).style(summary_style));
+
+        let shows_body = app.shows_body(notification);
+        if shows_body
+            && let Some(body) = &notification.body
+            && !body.is_empty()
+        {
+            for mut body_line in app.body_lines(body) {
+                body_line.spans.insert(0, Span::raw(DETAIL_INDENT));
+                lines.push(body_line.style(Style::new().fg(summary_color)));
+            }
+        }
+
+        if shows_body && let Some(source) = &notification.body_source {
+            let source = source.trim();
+            if !source.is_empty() {
+                lines.push(
+                    Line::from(format!(
+                        "{DETAIL_INDENT}{}",
+                        truncate(source, app.body_truncate_width)
+                    ))
+                    .style(Style::new()),
+                );
+            }
+        }
+        let mut item = ListItem::new(lines);
+        if notification
+            .event_uid
+            .as_ref()
+            .is_some_and(|event_uid| app.marked.contains(event_uid))
+        {
+            item = item.style(Style::new().bg(Color::Blue).add_modifier(Modifier::BOLD));
+        }
+        items.push(item);
+        let spacer = app.spacer_after(idx);
+        if spacer > 0 {
+            // Dedicated spacer item so it doesn't get selected/highlighted.
+            items.push(ListItem::new(vec![Line::from(""); usize::from(spacer)]));
+        }
+    }
+
+    app.scroll_to_selection(list_inner.height);
+
+    let spacer = app.spacer_rows();
+    let mut state = ListState::default().with_offset(visual_index_for(app.list_offset, spacer));
+    if !app.notifications.is_empty() {
+        state.select(Some(visual_index_for(app.selected, spacer)));
+    }
+
+    let list = List::new(items)
+        .highlight_style(Style::new().bg(Color::DarkGray))
+        .highlight_symbol(HIGHLIGHT_SYMBOL);
+    frame.render_widget(list_block, chunks[0]);
+    frame.render_stateful_widget(list, list_inner, &mut state);
+
+    if let Some(header_area) = header_area {
+        // Mirrors the item layout: badge, [app], time, summary.
+        let time_label = match app.time_display {
+            TimeDisplay::Clock => "Time ",
+            TimeDisplay::Relative => "Age  ",
+        };
+        frame.render_widget(
+            Paragraph::new(format!("{HIGHLIGHT_SYMBOL}St  [App] {time_label} Summary")).style(
+                Style::new()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            ),
+            header_area,
+        );
+    }
+
+    if app.notifications.is_empty() && list_inner.height > 0 {
+        let message_area = Rect {
+            y: list_inner.y + list_inner.height.saturating_sub(1) / 2,
+            height: 1,
+            ..list_inner
+        };
+        frame.render_widget(
+            Paragraph::new(app.empty_message())
+                .alignment(Alignment::Center)
+                .style(Style::new().fg(Color::Gray).add_modifier(Modifier::DIM)),
+            message_area,
+        );
+    }
+
+    let prompt = if app.search_input {
+        Some(("/", app.search_query.as_str()))
+    } else if let Some(edit) = &app.edit_input {
+        Some(match edit.field {
+            EditField::Summary => ("summary: ", edit.summary.as_str()),
+            EditField::Body => ("body: ", edit.body.as_str()),
+        })
+    } else {
+        app.id_input.as_deref().map(|input| (":", input))
+    };
+    let status_line = if let Some((prefix, input)) = prompt {
+        Line::from(vec![
+            Span::styled(prefix, Style::new().fg(Color::Cyan)),
+            Span::raw(input.to_string()),
+            Span::styled("_", Style::new().add_modifier(Modifier::SLOW_BLINK)),
+        ])
+    } else {
+        Line::from(app.status.clone()).style(Style::new().fg(Color::DarkGray))
+    };
+    frame.render_widget(Paragraph::new(status_line), chunks[1]);
+
+    let legend = Paragraph::new(legend_text(app, usize::from(chunks[2].width)))
+        .alignment(Alignment::Center)
+        .style(Style::new().fg(Color::Cyan))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(legend, chunks[2]);
+}
+
+/// Legend entry: the actions whose keys are shown (joined by `/`) and what
+/// they do.
+type LegendHint = (&'static [KeyAction], &'static str);
+
+const LEGEND_FULL: [&[LegendHint]; 2] = [
+    &[
+        (&[KeyAction::ToggleFilter], "Missed/History/Open"),
+        (&[KeyAction::Dismiss], "Mark User Dismissed"),
+        (&[KeyAction::DismissAll], "All Shown"),
+        (&[KeyAction::Delete], "Delete"),
+        (&[KeyAction::Visual], "Visual"),
+        (&[KeyAction::Copy], "Copy"),
+        (&[KeyAction::Edit], "Edit"),
+        (&[KeyAction::Refresh], "Refresh"),
+        (&[KeyAction::ToggleHeader], "Header"),
+        (&[KeyAction::Quit], "Quit"),
+    ],
+    &[
+        (&[KeyAction::Prev], "Up"),
+        (&[KeyAction::Next], "Down"),
+        (&[KeyAction::PageUp, KeyAction::PageDown], "Page"),
+        (&[KeyAction::First], "Top"),
+        (&[KeyAction::Last], "Bottom"),
+        (
+            &[KeyAction::NextSameApp, KeyAction::PrevSameApp],
+            "Same App",
+        ),
+        (&[KeyAction::ToggleTime], "Clock/Relative Time"),
+        (&[KeyAction::ToggleSort], "Sort"),
+        (&[KeyAction::Today], "Today"),
+        (&[KeyAction::Compact], "Compact"),
+        (&[KeyAction::ToggleBody], "Body"),
+        (&[KeyAction::Search], "Search"),
+        (&[KeyAction::JumpToId], "Id"),
+    ],
+];
+// Most important first: hints that do not fit the two rows are dropped.
+const LEGEND_SHORT: &[LegendHint] = &[
+    (&[KeyAction::Quit], "Quit"),
+    (&[KeyAction::ToggleFilter], "Mode"),
+    (&[KeyAction::Next, KeyAction::Prev], "Move"),
+    (&[KeyAction::Search], "Search"),
+    (&[KeyAction::Dismiss], "Dismiss"),
+    (&[KeyAction::DismissAll], "Dismiss all"),
+    (&[KeyAction::Delete], "Del"),
+    (&[KeyAction::Copy], "Copy"),
+    (&[KeyAction::Edit], "Edit"),
+    (&[KeyAction::Refresh], "Refresh"),
+    (&[KeyAction::Visual], "Visual"),
+    (&[KeyAction::ToggleBody], "Body"),
+    (&[KeyAction::Compact], "Compact"),
+    (&[KeyAction::ToggleTime], "Time"),
+    (&[KeyAction::ToggleSort], "Sort"),
+    (&[KeyAction::Today], "Today"),
+    (&[KeyAction::NextSameApp, KeyAction::PrevSameApp], "App"),
+    (&[KeyAction::First, KeyAction::Last], "Ends"),
+    (&[KeyAction::PageUp, KeyAction::PageDown], "Page"),
+    (&[KeyAction::ToggleHeader], "Header"),
+    (&[KeyAction::JumpToId], "Id"),
+];
+const LEGEND_SEPARATOR: &str = " | ";
+
+/// Legend hints with the keys currently bound; a hint whose actions have no
+/// key is left out.
+fn legend_hints(app: &App, hints: &[LegendHint]) -> Vec<String> {
+    hints
+        .iter()
+        .filter_map(|(actions, label)| {
+            let keys = actions
+                .iter()
+                .map(|action| app.first_key(*action))
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("{} {label}", keys.join("/")))
+        })
+        .collect()
+}
+
+/// The full two-line legend when it fits `width`, otherwise short hints
+/// packed into as few lines (at most two) as the width allows.
+fn legend_text(app: &App, width: usize) -> String {
+    let full = LEGEND_FULL
+        .iter()
+        .map(|hints| legend_hints(app, hints).join(LEGEND_SEPARATOR))
+        .collect::<Vec<_>>();
+    if full.iter().all(|line| line.chars().count() <= width) {
+        return full.join("\n");
+    }
+
+    let mut lines: Vec<String> = vec![String::new()];
+    for hint in legend_hints(app, LEGEND_SHORT) {
+        let current = lines.last_mut().expect("legend has a line");
+        let needed = if current.is_empty() {
+            hint.chars().count()
+        } else {
+            current.chars().count() + LEGEND_SEPARATOR.len() + hint.chars().count()
+        };
+        if needed <= width {
+            if !current.is_empty() {
+                current.push_str(LEGEND_SEPARATOR);
             }
-        } else if app.last_refresh.elapsed() >= AUTO_REFRESH_EVERY {
-            app.refresh();
+            current.push_str(&hint);
+        } else if lines.len() < LEGEND_FULL.len() && hint.chars().count() <= width {
+            lines.push(hint);
         }
     }
+    lines.join("\n")
 }
 
-fn handle_mouse_event(app: &mut App, mouse: MouseEvent, terminal_area: Rect) {
-    match mouse.kind {
-        MouseEventKind::Down(MouseButton::Left) => {
-            select_notification_at(app, mouse.column, mouse.row, terminal_area);
+/// Splits a searched field into spans with the query emphasized on top of
+/// `style`, by the rule `search_score` matched it with: every case-insensitive
+/// occurrence for short queries, the fuzzy match's characters otherwise.
+fn highlight_matches(
+    text: &str,
+    query: &str,
+    matcher: &SkimMatcherV2,
+    style: Style,
+) -> Vec<Span<'static>> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut marked = vec![false; chars.len()];
+    let needle = query.chars().collect::<Vec<_>>();
+    if needle.is_empty() {
+        // Nothing to emphasize.
+    } else if needle.len() < FUZZY_MIN_QUERY_CHARS {
+        let same = |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+        let mut index = 0;
+        while index + needle.len() <= chars.len() {
+            let window = &chars[index..index + needle.len()];
+            if window.iter().zip(&needle).all(|(a, b)| same(*a, *b)) {
+                marked[index..index + needle.len()].fill(true);
+                index += needle.len();
+            } else {
+                index += 1;
+            }
+        }
+    } else if let Some((_, indices)) = matcher.fuzzy_indices(text, query) {
+        for index in indices {
+            if let Some(mark) = marked.get_mut(index) {
+                *mark = true;
+            }
         }
-        MouseEventKind::ScrollDown => app.select_next(),
-        MouseEventKind::ScrollUp => app.select_previous(),
-        _ => {}
     }
-}
 
-fn select_notification_at(app: &mut App, column: u16, row: u16, terminal_area: Rect) {
-    if app.notifications.is_empty() {
-        return;
+    let matched = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (ch, is_matched) in chars.into_iter().zip(marked) {
+        if is_matched != run_matched && !run.is_empty() {
+            let run_style = if run_matched { matched } else { style };
+            spans.push(Span::styled(std::mem::take(&mut run), run_style));
+        }
+        run_matched = is_matched;
+        run.push(ch);
     }
+    if !run.is_empty() {
+        spans.push(Span::styled(run, if run_matched { matched } else { style }));
+    }
+    spans
+}
 
-    let list_inner = list_inner_area(terminal_area);
-    if list_inner.width == 0 || list_inner.height == 0 {
-        return;
+fn search_score(matcher: &SkimMatcherV2, notification: &Notification, query: &str) -> Option<i64> {
+    let fields = [
+        Some(notification.summary.as_str()),
+        notification.app_name.as_deref(),
+    ];
+
+    if query.chars().count() < FUZZY_MIN_QUERY_CHARS {
+        let needle = query.to_lowercase();
+        return fields
+            .into_iter()
+            .flatten()
+            .any(|field| field.to_lowercase().contains(&needle))
+            .then_some(0);
     }
-    if column < list_inner.x
-        || column >= list_inner.x + list_inner.width
-        || row < list_inner.y
-        || row >= list_inner.y + list_inner.height
-    {
-        return;
+
+    fields
+        .into_iter()
+        .flatten()
+        .filter_map(|field| matcher.fuzzy_match(field, query))
+        .max()
+}
+
+fn close_reason_badge(close_reason_code: Option<u32>) -> &'static str {
+    match close_reason_code {
+        None => "[ ]",
+        Some(1) => "[⏰]",
+        Some(2) => "[✓]",
+        Some(3) => "[↩]",
+        Some(_) => "[?]",
     }
+}
 
-    let mut y = row - list_inner.y;
-    for (idx, notification) in app.notifications.iter().enumerate() {
-        let item_height = notification_item_height(notification);
-        if y < item_height {
-            app.selected = idx;
-            return;
-        }
-        y -= item_height;
+fn format_relative_age(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    if seconds < 60 {
+        format!("{seconds}s ago")
+    } else if seconds < 60 * 60 {
+        format!("{}m ago", seconds / 60)
+    } else if seconds < 24 * 60 * 60 {
+        format!("{}h ago", seconds / (60 * 60))
+    } else {
+        format!("{}d ago", seconds / (24 * 60 * 60))
+    }
+}
 
-        if idx + 1 < app.notifications.len() {
-            // Spacer row between notifications.
-            if y == 0 {
-                return;
-            }
-            y -= 1;
-        }
+/// Epoch seconds of midnight today in `timezone`, or in the local zone when
+/// none is configured.
+fn local_day_start_epoch(timezone: Option<Tz>) -> Option<i64> {
+    match timezone {
+        Some(timezone) => day_start_epoch(&timezone),
+        None => day_start_epoch(&Local),
     }
 }
 
-fn list_inner_area(terminal_area: Rect) -> Rect {
-    let area = terminal_area.inner(Margin {
-        horizontal: 1,
-        vertical: 1,
-    });
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(3), Constraint::Length(2)])
-        .split(area);
-
-    Block::bordered()
-        .padding(Padding::new(0, 0, 1, 0))
-        .inner(chunks[0])
-}
-
-fn notification_item_height(notification: &Notification) -> u16 {
-    let source_lines = notification
-        .body_source
-        .as_deref()
-        .map(|source| usize::from(!source.trim().is_empty()))
-        .unwrap_or(0);
-    let body_lines = notification
-        .body
-        .as_deref()
-        .map(|body| {
-            body.lines()
-                .map(str::trim)
-                .filter(|line| !line.is_empty())
-                .count()
-        })
-        .unwrap_or(0);
+fn day_start_epoch<Z: TimeZone>(zone: &Z) -> Option<i64> {
+    let midnight = Utc::now()
+        .with_timezone(zone)
+        .date_naive()
+        .and_hms_opt(0, 0, 0)?;
+    zone.from_local_datetime(&midnight)
+        .earliest()
+        .map(|time| time.timestamp())
+}
 
-    1 + u16::try_from(source_lines + body_lines).unwrap_or(u16::MAX - 1)
+/// Formats `time` in `timezone`, or in the local zone when none is configured.
+fn format_in_zone(time: DateTime<Utc>, timezone: Option<Tz>, format: &str) -> String {
+    match timezone {
+        Some(timezone) => time.with_timezone(&timezone).format(format).to_string(),
+        None => time.with_timezone(&Local).format(format).to_string(),
+    }
 }
 
-fn render_ui(frame: &mut Frame, app: &App) {
-    let area = frame.area().inner(Margin {
-        horizontal: 1,
-        vertical: 1,
-    });
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(3), Constraint::Length(2)])
-        .split(area);
+fn now_epoch() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
 
-    let mut items: Vec<ListItem> = Vec::new();
-    for (idx, notification) in app.notifications.iter().enumerate() {
-        let mut lines = Vec::new();
-        let summary_color = if notification.is_undismissed {
-            Color::Yellow
-        } else {
-            Color::Green
-        };
-        let summary = match notification.time_hhmm.as_deref() {
-            Some(time) if !time.is_empty() => format!("{time}  {}", notification.summary),
-            _ => notification.summary.clone(),
-        };
-        lines.push(Line::from(summary).style(Style::new().fg(summary_color)));
-
-        if let Some(body) = &notification.body {
-            if !body.is_empty() {
-                for body_line in body.lines().map(str::trim).filter(|line| !line.is_empty()) {
-                    lines.push(
-                        Line::from(format!("{DETAIL_INDENT}{}", truncate(body_line, 112)))
-                            .style(Style::new().fg(summary_color)),
-                    );
-                }
+/// Word-wraps `input` to `width` characters, hard-splitting words that are
+/// longer than a full line.
+fn wrap_text(input: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+
+    for mut word in input.split_whitespace() {
+        let mut word_width = word.width();
+        if current_width > 0 && current_width + 1 + word_width > width {
+            lines.push(std::mem::take(&mut current));
+            current_width = 0;
+        }
+        while word_width > width {
+            if current_width > 0 {
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
             }
+            let (head, rest) = split_at_width(word, width);
+            lines.push(head.to_string());
+            word = rest;
+            word_width = word.width();
+        }
+        if word.is_empty() {
+            continue;
+        }
+        if current_width > 0 {
+            current.push(' ');
+            current_width += 1;
         }
+        current_width += word_width;
+        current.push_str(word);
+    }
 
-        if let Some(source) = &notification.body_source {
-            let source = source.trim();
-            if !source.is_empty() {
-                lines.push(
-                    Line::from(format!("{DETAIL_INDENT}{}", truncate(source, 112)))
-                        .style(Style::new()),
-                );
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Pango tags removed from bodies, with the modifier each one renders as.
+const PANGO_TAGS: &[(&str, Modifier)] = &[
+    ("b", Modifier::BOLD),
+    ("i", Modifier::ITALIC),
+    ("u", Modifier::UNDERLINED),
+    ("s", Modifier::CROSSED_OUT),
+    ("a", Modifier::UNDERLINED),
+    ("big", Modifier::empty()),
+    ("small", Modifier::empty()),
+    ("sub", Modifier::empty()),
+    ("sup", Modifier::empty()),
+    ("tt", Modifier::empty()),
+    ("span", Modifier::empty()),
+    ("img", Modifier::empty()),
+    ("markup", Modifier::empty()),
+];
+
+enum PangoTag {
+    Open(Modifier),
+    Close,
+    SelfClosing,
+}
+
+/// Resolves Pango markup, entities, and ANSI escapes in `body` into plain
+/// characters and the style of each. Styles are only set when `render` is on;
+/// otherwise the markup is just dropped.
+fn parse_body_markup(body: &str, render: bool) -> Vec<(char, Style)> {
+    let mut styled = Vec::with_capacity(body.len());
+    let mut style = Style::new();
+    // Style in effect before each open tag, restored by its closing tag.
+    let mut open_tags = Vec::new();
+    let mut rest = body;
+
+    while let Some(ch) = rest.chars().next() {
+        if ch == '\u{1b}' {
+            let (len, sgr) = ansi_sequence(rest);
+            if render && let Some(params) = sgr {
+                style = apply_sgr(style, params);
             }
+            rest = &rest[len..];
+            continue;
+        }
+        if ch == '<'
+            && let Some((len, tag)) = pango_tag(rest)
+        {
+            if render {
+                match tag {
+                    PangoTag::Open(modifier) => {
+                        open_tags.push(style);
+                        style = style.add_modifier(modifier);
+                    }
+                    PangoTag::Close => {
+                        if let Some(previous) = open_tags.pop() {
+                            style = previous;
+                        }
+                    }
+                    PangoTag::SelfClosing => {}
+                }
+            }
+            rest = &rest[len..];
+            continue;
         }
-        items.push(ListItem::new(lines));
-        if idx + 1 < app.notifications.len() {
-            // Dedicated spacer row so it doesn't get selected/highlighted.
-            items.push(ListItem::new(Line::from("")));
+        if ch == '&'
+            && let Some((len, decoded)) = markup_entity(rest)
+        {
+            styled.push((decoded, style));
+            rest = &rest[len..];
+            continue;
         }
+        styled.push((ch, style));
+        rest = &rest[ch.len_utf8()..];
     }
+    styled
+}
 
-    let title = format!(
-        " Notifications | mode: {} | count: {} ",
-        app.filter.label(),
-        app.notifications.len()
-    );
-
-    let mut state = ListState::default();
-    if !app.notifications.is_empty() {
-        state.select(Some(app.selected * 2));
+/// Recognizes a known Pango tag at the start of `input`. Other `<...>` text
+/// (e.g. `Name <user@host>`) is left alone.
+fn pango_tag(input: &str) -> Option<(usize, PangoTag)> {
+    let end = input.find('>')?;
+    let inner = &input[1..end];
+    if inner.contains(['<', '\n']) {
+        return None;
     }
+    let closing = inner.starts_with('/');
+    let name_and_attrs = inner.trim_start_matches('/');
+    let name_len = name_and_attrs
+        .find(|ch: char| !ch.is_ascii_alphanumeric())
+        .unwrap_or(name_and_attrs.len());
+    let (name, attrs) = name_and_attrs.split_at(name_len);
+    if !(attrs.is_empty() || attrs.starts_with([' ', '/'])) {
+        return None;
+    }
+    let (_, modifier) = PANGO_TAGS
+        .iter()
+        .find(|(tag, _)| tag.eq_ignore_ascii_case(name))?;
+    let tag = if closing {
+        PangoTag::Close
+    } else if inner.ends_with('/') {
+        PangoTag::SelfClosing
+    } else {
+        PangoTag::Open(*modifier)
+    };
+    Some((end + 1, tag))
+}
 
-    let list = List::new(items)
-        .block(
-            Block::bordered()
-                .title(title)
-                .border_style(Style::new().fg(Color::Green))
-                .padding(Padding::new(0, 0, 1, 0)),
-        )
-        .highlight_style(Style::new().bg(Color::DarkGray))
-        .highlight_symbol("  ");
-    frame.render_stateful_widget(list, chunks[0], &mut state);
+/// Decodes the XML entities Pango markup requires (`&amp;`, `&#38;`, ...).
+fn markup_entity(input: &str) -> Option<(usize, char)> {
+    let (end, _) = input.char_indices().take(12).find(|(_, ch)| *ch == ';')?;
+    let decoded = match &input[1..end] {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        name => {
+            let code = name.strip_prefix('#')?;
+            let value = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse::<u32>().ok()?,
+            };
+            char::from_u32(value)?
+        }
+    };
+    Some((end + 1, decoded))
+}
 
-    let legend = Paragraph::new(
-        "f Show History/Missed | d Mark User Dismissed | y Copy Body | r Refresh | q Quit\nk,Up Up | j,Down Down | g Top | G Bottom",
-    )
-    .alignment(Alignment::Center)
-    .style(Style::new().fg(Color::Cyan))
-    .wrap(Wrap { trim: true });
-    frame.render_widget(legend, chunks[1]);
+/// Length of the escape sequence at the start of `input`, plus its parameters
+/// when it is an SGR (`ESC [ ... m`) sequence.
+fn ansi_sequence(input: &str) -> (usize, Option<&str>) {
+    let bytes = input.as_bytes();
+    match bytes.get(1) {
+        Some(b'[') => match bytes[2..]
+            .iter()
+            .position(|byte| (0x40..=0x7e).contains(byte))
+        {
+            Some(position) => {
+                let final_index = position + 2;
+                let sgr = (bytes[final_index] == b'm').then(|| &input[2..final_index]);
+                (final_index + 1, sgr)
+            }
+            None => (input.len(), None),
+        },
+        Some(_) => (
+            1 + input[1..].chars().next().map_or(0, char::len_utf8),
+            None,
+        ),
+        None => (1, None),
+    }
+}
+
+/// Applies SGR parameters (bold, italic, 16/256/RGB colors, resets) to `style`.
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    const BASIC: [Color; 8] = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::Gray,
+    ];
+    const BRIGHT: [Color; 8] = [
+        Color::DarkGray,
+        Color::LightRed,
+        Color::LightGreen,
+        Color::LightYellow,
+        Color::LightBlue,
+        Color::LightMagenta,
+        Color::LightCyan,
+        Color::White,
+    ];
+
+    let mut codes = params
+        .split(';')
+        .map(|code| code.parse::<u8>().unwrap_or(0));
+    while let Some(code) = codes.next() {
+        style = match code {
+            0 => Style::new(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            7 => style.add_modifier(Modifier::REVERSED),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(BASIC[usize::from(code - 30)]),
+            40..=47 => style.bg(BASIC[usize::from(code - 40)]),
+            90..=97 => style.fg(BRIGHT[usize::from(code - 90)]),
+            100..=107 => style.bg(BRIGHT[usize::from(code - 100)]),
+            39 => Style { fg: None, ..style },
+            49 => Style { bg: None, ..style },
+            38 | 48 => {
+                let color = match codes.next() {
+                    Some(5) => codes.next().map(Color::Indexed),
+                    Some(2) => match (codes.next(), codes.next(), codes.next()) {
+                        (Some(r), Some(g), Some(b)) => Some(Color::Rgb(r, g, b)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match (code, color) {
+                    (38, Some(color)) => style.fg(color),
+                    (_, Some(color)) => style.bg(color),
+                    _ => style,
+                }
+            }
+            _ => style,
+        };
+    }
+    style
 }
 
-fn truncate(input: &str, max_chars: usize) -> String {
-    let count = input.chars().count();
-    if count <= max_chars {
+/// Shortens `input` to `max_width` terminal columns with an ellipsis, counting
+/// wide characters (CJK, emoji) as two; `0` disables truncation.
+fn truncate(input: &str, max_width: usize) -> String {
+    if max_width == 0 || input.width() <= max_width {
         return input.to_string();
     }
-    input.chars().take(max_chars).collect::<String>() + "..."
+    split_at_width(input, max_width).0.to_string() + "..."
+}
+
+/// Splits `text` after the longest prefix that fits in `width` columns, always
+/// keeping at least one character in the prefix so wrapping makes progress.
+fn split_at_width(text: &str, width: usize) -> (&str, &str) {
+    let mut used = 0;
+    for (index, ch) in text.char_indices() {
+        used += ch.width().unwrap_or(0);
+        if used > width && index > 0 {
+            return text.split_at(index);
+        }
+    }
+    (text, "")
 }
 
-fn fetch_notifications(filter: FilterMode) -> Result<Vec<Notification>, String> {
-    load_notifications_from_jsonl(filter)
+/// Notifications shown in `filter`, plus the missed (auto-dismissed) count
+/// across all modes, from a single read of the log.
+fn fetch_notifications(
+    filter: FilterMode,
+    sort_order: SortOrder,
+) -> Result<(Vec<Notification>, usize), String> {
+    load_notifications_from_jsonl(filter, sort_order)
 }
 
-fn load_notifications_from_jsonl(filter: FilterMode) -> Result<Vec<Notification>, String> {
+fn load_notifications_from_jsonl(
+    filter: FilterMode,
+    sort_order: SortOrder,
+) -> Result<(Vec<Notification>, usize), String> {
     let path = notification_log_path().ok_or_else(|| String::from("could not resolve log path"))?;
     if !path.exists() {
-        return Ok(Vec::new());
+        return Ok((Vec::new(), 0));
     }
 
     let records = read_log_records(&path)?;
-    let merged = aggregate_log_records(&records);
-    Ok(notifications_from_log_records(&merged, filter))
+    let merged = aggregate_log_records(&records, sort_order);
+    let hide_empty = app_config::load_or_create().hide_empty_summary;
+    let notifications = notifications_from_log_records(&merged, filter, hide_empty);
+    let missed_count = match filter {
+        FilterMode::AutoDismissed => notifications.len(),
+        FilterMode::All => notifications
+            .iter()
+            .filter(|notification| notification.is_undismissed)
+            .count(),
+        FilterMode::Open => {
+            notifications_from_log_records(&merged, FilterMode::AutoDismissed, hide_empty).len()
+        }
+    };
+    Ok((notifications, missed_count))
+}
+
+/// Modification time and size of the log, or `None` when it cannot be read.
+fn log_file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
 }
 
 fn notification_log_path() -> Option<PathBuf> {
@@ -761,52 +2634,159 @@ fn is_auto_dismissed_record(record: &LogRecord) -> bool {
     record.close_reason_code == Some(1) || record.close_reason.as_deref() == Some("expired")
 }
 
-fn mark_notification_user_dismissed(event_uid: &str) -> Result<String, String> {
+fn mark_notifications_user_dismissed(event_uids: &[String]) -> Result<String, String> {
     let path = notification_log_path().ok_or_else(|| String::from("could not resolve log path"))?;
     let records = read_log_records(&path)?;
-    let merged = aggregate_log_records(&records);
+    let merged = aggregate_log_records(&records, SortOrder::Closed);
     let missed_before = merged
         .iter()
         .filter(|record| is_auto_dismissed_record(record))
         .count();
 
-    let Some(current) = merged
+    let targets = merged
         .iter()
-        .find(|record| record.event_uid.as_deref() == Some(event_uid))
-    else {
-        return Err(String::from("target notification not found in log"));
-    };
-
-    let is_auto_dismissed = is_auto_dismissed_record(current);
-    if !is_auto_dismissed {
+        .filter(|record| {
+            record
+                .event_uid
+                .as_ref()
+                .is_some_and(|event_uid| event_uids.contains(event_uid))
+        })
+        .filter(|record| is_auto_dismissed_record(record))
+        .collect::<Vec<_>>();
+    if targets.is_empty() {
         return Err(String::from(
-            "selected notification is not currently auto-dismissed",
+            "no selected notification is currently auto-dismissed",
         ));
     }
 
-    let payload = serde_json::json!({
-        "event_uid": current.event_uid.clone(),
-        "id": current.id,
-        "close_reason_code": 2,
-        "close_reason": "dismissed-by-user",
-        "closed_epoch": current.closed_epoch,
-        "closed_hhmm": current.closed_hhmm.clone(),
+    let payloads = targets
+        .iter()
+        .map(|current| {
+            serde_json::json!({
+                "event_uid": current.event_uid.clone(),
+                "id": current.id,
+                "close_reason_code": 2,
+                "close_reason": "dismissed-by-user",
+                "closed_epoch": current.closed_epoch,
+                "closed_hhmm": current.closed_hhmm.clone(),
+            })
+        })
+        .collect::<Vec<_>>();
+    append_log_payloads(&path, &payloads)?;
+    if targets.len() == missed_before
+        && let Err(error) = trigger_refresh_signal(refresh_signal_channel())
+    {
+        eprintln!("warning: failed to trigger refresh signal: {error}");
+    }
+    Ok(match targets.len() {
+        1 => String::from("Marked selected notification as dismissed-by-user"),
+        count => format!("Marked {count} notifications as dismissed-by-user"),
+    })
+}
+
+/// Appends a record overriding the edited summary and/or body; merging puts
+/// it over the logged text. Returns `false` when nothing changed. An emptied
+/// field is left as it was, since blank log values read as missing.
+fn write_notification_edit(edit: &EditInput) -> Result<bool, String> {
+    let Some(payload) = edit_payload(edit) else {
+        return Ok(false);
+    };
+    let path = notification_log_path().ok_or_else(|| String::from("could not resolve log path"))?;
+    append_log_payloads(&path, &[payload])?;
+    Ok(true)
+}
+
+/// Log record for an edit, carrying only the fields that changed; `None` when
+/// nothing did. A field left empty keeps its old text.
+fn edit_payload(edit: &EditInput) -> Option<Value> {
+    let summary = edit.summary.trim();
+    let body = edit.body.trim();
+    let summary_changed = !summary.is_empty() && summary != edit.original_summary.trim();
+    let body_changed = !body.is_empty() && body != edit.original_body.trim();
+    if !summary_changed && !body_changed {
+        return None;
+    }
+
+    let mut payload = serde_json::json!({
+        "event_uid": edit.event_uid,
+        "id": edit.id,
     });
-    append_log_payload(&path, &payload)?;
-    if missed_before == 1 {
-        if let Err(error) = trigger_refresh_signal(refresh_signal_channel()) {
-            eprintln!("warning: failed to trigger refresh signal: {error}");
+    if summary_changed {
+        payload["summary"] = Value::from(summary);
+    }
+    if body_changed {
+        payload["body"] = Value::from(body);
+        // Keeps the text from being split into source and body on read.
+        if let Some(source) = &edit.body_source {
+            payload["body_source"] = Value::from(source.as_str());
+        }
+    }
+    Some(payload)
+}
+
+/// Removes every log line belonging to `event_uids`, rewriting the log via a
+/// temp file. Lines that fail to parse are kept untouched.
+fn delete_notifications(event_uids: &[String]) -> Result<usize, String> {
+    let path = notification_log_path().ok_or_else(|| String::from("could not resolve log path"))?;
+    ensure_plain_log(&path)?;
+    let content = fs::read_to_string(&path)
+        .map_err(|error| format!("failed to read {}: {error}", path.display()))?;
+
+    let lines = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let record = serde_json::from_str::<Value>(line)
+                .ok()
+                .and_then(|value| parse_log_record(&value));
+            (line, record)
+        })
+        .collect::<Vec<_>>();
+    let records = lines
+        .iter()
+        .filter_map(|(_, record)| record.clone())
+        .collect::<Vec<_>>();
+    let mut keys = record_event_keys(&records).into_iter();
+
+    let targets = event_uids.iter().collect::<HashSet<_>>();
+    let mut removed = HashSet::new();
+    let mut kept = String::with_capacity(content.len());
+    for (line, record) in &lines {
+        if record.is_some()
+            && let Some(key) = keys.next()
+            && targets.contains(&key)
+        {
+            removed.insert(key);
+            continue;
         }
+        kept.push_str(line);
+        kept.push('\n');
     }
-    Ok(String::from(
-        "Marked selected notification as dismissed-by-user",
-    ))
+    if removed.is_empty() {
+        return Err(String::from("selected notifications not found in log"));
+    }
+
+    app_config::replace_file(&path, |file| file.write_all(kept.as_bytes()))
+        .map_err(|error| format!("failed to rewrite {}: {error}", path.display()))?;
+
+    let _ = trigger_refresh_signal(refresh_signal_channel());
+    Ok(removed.len())
 }
 
 fn read_log_records(path: &PathBuf) -> Result<Vec<LogRecord>, String> {
     let file =
         File::open(path).map_err(|error| format!("failed to open {}: {error}", path.display()))?;
-    let reader = BufReader::new(file);
+    let mut reader = BufReader::new(file);
+    // Gzip archives (by magic bytes) are decompressed transparently.
+    let is_gzip = reader
+        .fill_buf()
+        .map_err(|error| format!("failed to read {}: {error}", path.display()))?
+        .starts_with(&GZIP_MAGIC);
+    let reader: Box<dyn BufRead> = if is_gzip {
+        Box::new(BufReader::new(MultiGzDecoder::new(reader)))
+    } else {
+        Box::new(reader)
+    };
     let mut records = Vec::new();
 
     for line in reader.lines() {
@@ -844,6 +2824,7 @@ fn parse_log_record(value: &Value) -> Option<LogRecord> {
         close_reason: json_string(value.get("close_reason")),
         closed_epoch: json_i64(value.get("closed_epoch")),
         closed_hhmm: json_string(value.get("closed_hhmm")),
+        seq: value.get("seq").and_then(Value::as_u64),
     })
 }
 
@@ -904,15 +2885,17 @@ fn split_body_fields(body_text: &str) -> (Option<String>, Option<String>) {
     }
 }
 
-fn aggregate_log_records(records: &[LogRecord]) -> Vec<LogRecord> {
+/// Merges records per notification, newest first by `sort_order`: with
+/// `Closed` the latest record of each notification (usually its close)
+/// counts, with `Opened` its arrival time.
+fn aggregate_log_records(records: &[LogRecord], sort_order: SortOrder) -> Vec<LogRecord> {
+    let keys = record_event_keys(records);
     let mut merged: HashMap<String, LogRecord> = HashMap::new();
-    let mut order: HashMap<String, (i64, usize)> = HashMap::new();
+    // Newest by (epoch, seq, line), matching `notilog`'s aggregation.
+    let mut order: HashMap<String, (i64, u64, usize)> = HashMap::new();
 
     for (index, record) in records.iter().enumerate() {
-        let key = record
-            .event_uid
-            .clone()
-            .unwrap_or_else(|| format!("legacy:{}:{index}", record.id));
+        let key = keys[index].clone();
         let entry = merged
             .entry(key.clone())
             .or_insert_with(|| LogRecord::empty(record.id));
@@ -921,39 +2904,100 @@ fn aggregate_log_records(records: &[LogRecord]) -> Vec<LogRecord> {
         }
         entry.merge_from(record);
 
-        let event_epoch = log_record_epoch(record).unwrap_or(0);
+        let rank = (
+            log_record_epoch(record).unwrap_or(0),
+            record.seq.unwrap_or(0),
+            index,
+        );
         order
             .entry(key)
-            .and_modify(|best| {
-                if event_epoch > best.0 || (event_epoch == best.0 && index > best.1) {
-                    *best = (event_epoch, index);
-                }
-            })
-            .or_insert((event_epoch, index));
+            .and_modify(|best| *best = (*best).max(rank))
+            .or_insert(rank);
+    }
+    if sort_order == SortOrder::Opened {
+        for (key, record) in &merged {
+            if let (Some(best), Some(opened)) = (order.get_mut(key), log_record_epoch(record)) {
+                best.0 = opened;
+            }
+        }
     }
 
     let mut values: Vec<LogRecord> = merged.into_values().collect();
     values.sort_by(|left, right| {
         let left_key = left.event_uid.clone().unwrap_or_default();
         let right_key = right.event_uid.clone().unwrap_or_default();
-        let left_order = order.get(&left_key).copied().unwrap_or((0, 0));
-        let right_order = order.get(&right_key).copied().unwrap_or((0, 0));
-        right_order
-            .0
-            .cmp(&left_order.0)
-            .then_with(|| right_order.1.cmp(&left_order.1))
+        let left_order = order.get(&left_key).copied().unwrap_or_default();
+        let right_order = order.get(&right_key).copied().unwrap_or_default();
+        right_order.cmp(&left_order)
     });
     values
 }
 
-fn notifications_from_log_records(records: &[LogRecord], filter: FilterMode) -> Vec<Notification> {
+/// Same keying as `notilog`: orphan close records attach to the latest open
+/// notification with the same id inside `ORPHAN_CLOSE_WINDOW_SECS`.
+fn record_event_keys(records: &[LogRecord]) -> Vec<String> {
+    let mut open_by_id: HashMap<u32, (String, Option<i64>)> = HashMap::new();
+    let mut keys = Vec::with_capacity(records.len());
+
+    for (index, record) in records.iter().enumerate() {
+        let is_close = record.close_reason_code.is_some();
+        let key = match &record.event_uid {
+            Some(event_uid) => event_uid.clone(),
+            None if is_close => open_by_id
+                .get(&record.id)
+                .filter(|(_, opened)| within_orphan_window(*opened, record.closed_epoch))
+                .map(|(key, _)| key.clone())
+                .unwrap_or_else(|| format!("legacy:{}:{index}", record.id)),
+            None => format!("legacy:{}:{index}", record.id),
+        };
+
+        if is_close {
+            if open_by_id
+                .get(&record.id)
+                .is_some_and(|(open_key, _)| *open_key == key)
+            {
+                open_by_id.remove(&record.id);
+            }
+        } else if record.epoch.is_some() {
+            open_by_id.insert(record.id, (key.clone(), record.epoch));
+        }
+        keys.push(key);
+    }
+
+    keys
+}
+
+fn within_orphan_window(opened: Option<i64>, closed: Option<i64>) -> bool {
+    match (opened, closed) {
+        (Some(opened), Some(closed)) => (0..=ORPHAN_CLOSE_WINDOW_SECS).contains(&(closed - opened)),
+        _ => true,
+    }
+}
+
+/// `hide_empty` drops records whose summary and body are both blank.
+fn notifications_from_log_records(
+    records: &[LogRecord],
+    filter: FilterMode,
+    hide_empty: bool,
+) -> Vec<Notification> {
+    let is_blank =
+        |text: &Option<String>| text.as_deref().is_none_or(|text| text.trim().is_empty());
     records
         .iter()
         .filter_map(|record| {
+            if hide_empty && is_blank(&record.summary) && is_blank(&record.body) {
+                return None;
+            }
             let is_auto_dismissed = record.close_reason_code == Some(1)
                 || record.close_reason.as_deref() == Some("expired");
-            if matches!(filter, FilterMode::AutoDismissed) && !is_auto_dismissed {
-                return None;
+            let is_open = record.epoch.is_some()
+                && record.close_reason_code.is_none()
+                && record.closed_epoch.is_none();
+            match filter {
+                FilterMode::All => {}
+                FilterMode::AutoDismissed if !is_auto_dismissed => return None,
+                FilterMode::Open if !is_open => return None,
+                FilterMode::AutoDismissed | FilterMode::Open => {}
             }
 
             let summary = record
@@ -963,7 +3007,10 @@ fn notifications_from_log_records(records: &[LogRecord], filter: FilterMode) ->
             let mut notification = Notification::new(record.id, summary);
             notification.event_uid = record.event_uid.clone();
             notification.is_undismissed = is_auto_dismissed;
+            notification.close_reason_code = record.close_reason_code;
             notification.time_hhmm = record.hhmm.clone().or_else(|| record.closed_hhmm.clone());
+            notification.epoch = record.epoch.or(record.closed_epoch);
+            notification.closed_epoch = record.closed_epoch;
             notification.app_name = record.app_name.clone();
             notification.body_source = record.body_source.clone();
             notification.body = record.body.clone();
@@ -976,85 +3023,285 @@ fn log_record_epoch(record: &LogRecord) -> Option<i64> {
     record.epoch.or(record.closed_epoch)
 }
 
-fn append_log_payload(path: &PathBuf, payload: &Value) -> Result<(), String> {
+/// Compressed archives are read-only; only the plain live log is written.
+fn ensure_plain_log(path: &PathBuf) -> Result<(), String> {
+    let mut magic = [0u8; 2];
+    if File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|()| magic == GZIP_MAGIC)
+    {
+        return Err(format!(
+            "{} is gzip-compressed and can only be read",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Appends one line per payload through a single open of the log.
+fn append_log_payloads(path: &PathBuf, payloads: &[Value]) -> Result<(), String> {
+    ensure_plain_log(path)?;
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
         .open(path)
         .map_err(|error| format!("failed to open {} for append: {error}", path.display()))?;
-    serde_json::to_writer(&mut file, payload)
-        .map_err(|error| format!("failed to write JSON payload: {error}"))?;
-    writeln!(file).map_err(|error| format!("failed to append newline: {error}"))?;
+    for payload in payloads {
+        serde_json::to_writer(&mut file, payload)
+            .map_err(|error| format!("failed to write JSON payload: {error}"))?;
+        writeln!(file).map_err(|error| format!("failed to append newline: {error}"))?;
+    }
     file.flush()
         .map_err(|error| format!("failed to flush {}: {error}", path.display()))
 }
 
-fn copy_text_to_clipboard(text: &str) -> Result<&'static str, String> {
-    let mut last_error = String::from("no clipboard command available");
-
-    for (command, args) in [
-        ("wl-copy", Vec::<&str>::new()),
-        ("xclip", vec!["-selection", "clipboard"]),
-        ("xsel", vec!["--clipboard", "--input"]),
-    ] {
-        match run_clipboard_command(command, &args, text) {
-            Ok(()) => return Ok(command),
-            Err(error) => {
-                last_error = error;
-            }
-        }
-    }
-
-    Err(last_error)
-}
-
-fn run_clipboard_command(command: &str, args: &[&str], text: &str) -> Result<(), String> {
-    let mut child = match Command::new(command)
-        .args(args)
-        .stdin(Stdio::piped())
+fn spawn_open_command(command: &str) -> Result<(), String> {
+    Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()
-    {
-        Ok(child) => child,
-        Err(error) if error.kind() == io::ErrorKind::NotFound => {
-            return Err(format!("{command} not found"));
-        }
-        Err(error) => {
-            return Err(format!("could not start {command}: {error}"));
-        }
+        .map(|mut child| {
+            // Reap the shell in the background so it does not linger as a zombie.
+            thread::spawn(move || child.wait());
+        })
+        .map_err(|error| format!("could not start `{command}`: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use fuzzy_matcher::skim::SkimMatcherV2;
+    use ratatui::style::{Color, Modifier, Style};
+    use serde_json::json;
+
+    use super::app_config::parse_key_spec;
+    use super::{
+        EditField, EditInput, PangoTag, ansi_sequence, apply_sgr, edit_payload, highlight_matches,
+        markup_entity, pango_tag, parse_body_markup, visual_index_for,
     };
 
-    if let Some(stdin) = child.stdin.as_mut() {
-        stdin
-            .write_all(text.as_bytes())
-            .map_err(|error| format!("failed to write to {command}: {error}"))?;
-    } else {
-        return Err(format!("could not open stdin for {command}"));
+    fn plain_text(styled: &[(char, Style)]) -> String {
+        styled.iter().map(|(ch, _)| ch).collect()
     }
-    child.stdin.take();
 
-    let deadline = Instant::now() + Duration::from_millis(300);
-    loop {
-        match child.try_wait() {
-            Ok(Some(status)) => {
-                if status.success() {
-                    return Ok(());
-                }
-                return Err(format!("{command} exited with status {status}"));
-            }
-            Ok(None) => {
-                if Instant::now() >= deadline {
-                    thread::spawn(move || {
-                        let _ = child.wait();
-                    });
-                    return Ok(());
-                }
-                thread::sleep(Duration::from_millis(10));
-            }
-            Err(error) => {
-                return Err(format!("failed while waiting for {command}: {error}"));
-            }
+    fn edit(summary: &str, body: &str) -> EditInput {
+        EditInput {
+            event_uid: String::from("7_uid"),
+            id: 7,
+            field: EditField::Summary,
+            summary: summary.to_string(),
+            body: body.to_string(),
+            body_source: None,
+            original_summary: String::from("Build failed"),
+            original_body: String::from("exit code 2"),
         }
     }
+
+    #[test]
+    fn visual_index_for_skips_one_spacer_item_per_notification() {
+        assert_eq!(visual_index_for(0, 0), 0);
+        assert_eq!(visual_index_for(3, 0), 3);
+        assert_eq!(visual_index_for(0, 1), 0);
+        assert_eq!(visual_index_for(3, 1), 6);
+        // A taller spacer is still a single item.
+        assert_eq!(visual_index_for(3, 2), 6);
+    }
+
+    #[test]
+    fn parse_body_markup_nests_tags_and_restores_the_outer_style() {
+        let styled = parse_body_markup("<b>a<i>b</i>c</b>d", true);
+
+        assert_eq!(plain_text(&styled), "abcd");
+        let bold = Style::new().add_modifier(Modifier::BOLD);
+        assert_eq!(styled[0].1, bold);
+        assert_eq!(styled[1].1, bold.add_modifier(Modifier::ITALIC));
+        assert_eq!(styled[2].1, bold);
+        assert_eq!(styled[3].1, Style::new());
+    }
+
+    #[test]
+    fn parse_body_markup_keeps_unterminated_and_unknown_tags_as_text() {
+        let body = "Ann <ann@host> wrote 1 < 2 <b";
+        let styled = parse_body_markup(body, true);
+
+        assert_eq!(plain_text(&styled), body);
+        assert!(styled.iter().all(|(_, style)| *style == Style::new()));
+    }
+
+    #[test]
+    fn parse_body_markup_strips_everything_when_not_rendering() {
+        let styled = parse_body_markup("<b>hi</b> \u{1b}[1;31mred\u{1b}[0m &amp; <u>x</u>", false);
+
+        assert_eq!(plain_text(&styled), "hi red & x");
+        assert!(styled.iter().all(|(_, style)| *style == Style::new()));
+    }
+
+    #[test]
+    fn pango_tag_reads_open_close_and_self_closing_tags() {
+        assert!(matches!(
+            pango_tag("<span foreground='red'>x"),
+            Some((23, PangoTag::Open(_)))
+        ));
+        assert!(matches!(pango_tag("</B>"), Some((4, PangoTag::Close))));
+        assert!(matches!(
+            pango_tag("<img src='a.png'/>"),
+            Some((18, PangoTag::SelfClosing))
+        ));
+        assert!(pango_tag("<bold>").is_none());
+        assert!(pango_tag("<b\n>").is_none());
+        assert!(pango_tag("<b unterminated").is_none());
+    }
+
+    #[test]
+    fn markup_entity_decodes_named_and_numeric_entities() {
+        assert_eq!(markup_entity("&amp; rest"), Some((5, '&')));
+        assert_eq!(markup_entity("&lt;"), Some((4, '<')));
+        assert_eq!(markup_entity("&#38;"), Some((5, '&')));
+        assert_eq!(markup_entity("&#x41;"), Some((6, 'A')));
+        assert_eq!(markup_entity("&nbsp;"), None);
+        assert_eq!(markup_entity("& no terminator"), None);
+        assert_eq!(markup_entity("&#xZZ;"), None);
+    }
+
+    #[test]
+    fn ansi_sequence_measures_sgr_other_and_unterminated_escapes() {
+        assert_eq!(ansi_sequence("\u{1b}[1;31mtext"), (7, Some("1;31")));
+        assert_eq!(ansi_sequence("\u{1b}[2Ktext"), (4, None));
+        assert_eq!(ansi_sequence("\u{1b}(B"), (2, None));
+        assert_eq!(ansi_sequence("\u{1b}[31"), (4, None));
+        assert_eq!(ansi_sequence("\u{1b}"), (1, None));
+    }
+
+    #[test]
+    fn apply_sgr_sets_and_resets_modifiers_and_colors() {
+        let style = apply_sgr(Style::new(), "1;31;44");
+        assert_eq!(
+            style,
+            Style::new()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::Red)
+                .bg(Color::Blue)
+        );
+
+        assert_eq!(
+            apply_sgr(style, "22;39"),
+            Style::new()
+                .bg(Color::Blue)
+                .remove_modifier(Modifier::BOLD | Modifier::DIM)
+        );
+        assert_eq!(apply_sgr(style, "0"), Style::new());
+        assert_eq!(apply_sgr(style, ""), Style::new());
+        assert_eq!(
+            apply_sgr(Style::new(), "38;5;208;48;2;1;2;3;92"),
+            Style::new()
+                .fg(Color::Indexed(208))
+                .bg(Color::Rgb(1, 2, 3))
+                .fg(Color::LightGreen)
+        );
+    }
+
+    #[test]
+    fn edit_payload_is_none_when_nothing_changed() {
+        assert_eq!(edit_payload(&edit("Build failed", "exit code 2")), None);
+        // Surrounding whitespace is not a change.
+        assert_eq!(edit_payload(&edit(" Build failed ", "exit code 2\n")), None);
+    }
+
+    #[test]
+    fn edit_payload_carries_only_the_changed_summary() {
+        assert_eq!(
+            edit_payload(&edit("Build fixed", "exit code 2")),
+            Some(json!({ "event_uid": "7_uid", "id": 7, "summary": "Build fixed" }))
+        );
+    }
+
+    #[test]
+    fn edit_payload_keeps_body_source_with_a_changed_body() {
+        let mut input = edit("Build failed", "exit code 0");
+        input.body_source = Some(String::from("ci.example"));
+
+        assert_eq!(
+            edit_payload(&input),
+            Some(json!({
+                "event_uid": "7_uid",
+                "id": 7,
+                "body": "exit code 0",
+                "body_source": "ci.example",
+            }))
+        );
+    }
+
+    #[test]
+    fn edit_payload_ignores_an_emptied_field() {
+        assert_eq!(edit_payload(&edit("  ", "exit code 2")), None);
+        assert_eq!(
+            edit_payload(&edit("", "exit code 0")),
+            Some(json!({ "event_uid": "7_uid", "id": 7, "body": "exit code 0" }))
+        );
+    }
+
+    fn highlighted(text: &str, query: &str) -> Vec<(String, bool)> {
+        let matcher = SkimMatcherV2::default().ignore_case();
+        highlight_matches(text, query, &matcher, Style::new())
+            .into_iter()
+            .map(|span| (span.content.into_owned(), span.style != Style::new()))
+            .collect()
+    }
+
+    #[test]
+    fn highlight_matches_marks_every_occurrence_of_a_short_query() {
+        assert_eq!(
+            highlighted("Ab cab", "ab"),
+            [
+                (String::from("Ab"), true),
+                (String::from(" c"), false),
+                (String::from("ab"), true),
+            ]
+        );
+        assert_eq!(highlighted("xyz", "ab"), [(String::from("xyz"), false)]);
+    }
+
+    #[test]
+    fn highlight_matches_marks_fuzzy_positions_of_a_long_query() {
+        assert_eq!(
+            highlighted("Build failed", "bld"),
+            [
+                (String::from("B"), true),
+                (String::from("ui"), false),
+                (String::from("ld"), true),
+                (String::from(" failed"), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_key_spec_handles_names_and_modifiers() {
+        assert_eq!(
+            parse_key_spec("G"),
+            Some((KeyCode::Char('G'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key_spec(" PageDown "),
+            Some((KeyCode::PageDown, KeyModifiers::NONE))
+        );
+        assert_eq!(
+            parse_key_spec("C-n"),
+            Some((KeyCode::Char('n'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key_spec("ctrl-alt-x"),
+            Some((
+                KeyCode::Char('x'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ))
+        );
+        assert_eq!(
+            parse_key_spec("C--"),
+            Some((KeyCode::Char('-'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(parse_key_spec("f13"), None);
+        assert_eq!(parse_key_spec("hyper"), None);
+    }
 }